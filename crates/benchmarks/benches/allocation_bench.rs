@@ -0,0 +1,106 @@
+use crate::helper::{contact_positions, race_positions};
+use engine::dice::ALL_21;
+use engine::evaluator::{Evaluator, EvaluatorFake};
+use engine::position::Position;
+use mimalloc::MiMalloc;
+use std::alloc::{GlobalAlloc, Layout};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+mod helper;
+
+// This file doesn't measure time but counts allocations. It checks that reusing a buffer for move
+// generation, like during a rollout, allocates less and doesn't change the results.
+// Run it with `cargo bench --bench allocation_bench`.
+
+struct CountingAllocator;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { MiMalloc.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { MiMalloc.dealloc(ptr, layout) }
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { MiMalloc.realloc(ptr, layout, new_size) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the result of `f` and the number of allocations it needed.
+fn count_allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = f();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+fn move_generation(positions: &[Position]) {
+    let (expected, allocating) = count_allocations(|| {
+        let mut all = Vec::with_capacity(positions.len() * ALL_21.len());
+        for position in positions {
+            for (dice, _) in ALL_21.iter() {
+                all.push(position.all_positions_after_moving(dice).len());
+            }
+        }
+        all
+    });
+    let (actual, reusing) = count_allocations(|| {
+        let mut all = Vec::with_capacity(positions.len() * ALL_21.len());
+        let mut buffer = Vec::new();
+        for position in positions {
+            for (dice, _) in ALL_21.iter() {
+                position.all_positions_after_moving_into(dice, &mut buffer);
+                all.push(buffer.len());
+            }
+        }
+        all
+    });
+    assert_eq!(actual, expected);
+    assert!(reusing < allocating);
+    println!("generate moves: {allocating} allocations, reusing a buffer: {reusing} allocations.");
+}
+
+fn best_position(positions: &[Position]) {
+    let evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+    let (expected, allocating) = count_allocations(|| {
+        let mut all = Vec::with_capacity(positions.len() * ALL_21.len());
+        for position in positions {
+            for (dice, _) in ALL_21.iter() {
+                all.push(evaluator.best_position_by_equity(position, dice));
+            }
+        }
+        all
+    });
+    let (actual, reusing) = count_allocations(|| {
+        let mut all = Vec::with_capacity(positions.len() * ALL_21.len());
+        let mut buffer = Vec::new();
+        for position in positions {
+            for (dice, _) in ALL_21.iter() {
+                all.push(evaluator.best_position_by_equity_reusing(position, dice, &mut buffer));
+            }
+        }
+        all
+    });
+    assert_eq!(actual, expected);
+    assert!(reusing < allocating);
+    println!("best position: {allocating} allocations, reusing a buffer: {reusing} allocations.");
+}
+
+fn main() {
+    let contact = contact_positions();
+    let race = race_positions();
+    println!("1000 contact positions, 21 dice rolls:");
+    move_generation(&contact);
+    best_position(&contact);
+    println!("1000 race positions, 21 dice rolls:");
+    move_generation(&race);
+    best_position(&race);
+}
@@ -0,0 +1,89 @@
+use engine::dice::Dice;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+
+/// How close a candidate evaluator is to a reference, see [BenchmarkEvaluator::report].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BenchmarkReport {
+    pub number_of_positions: usize,
+    /// Average of the absolute differences between the cubeless equities of candidate and reference.
+    pub mean_absolute_equity_error: f32,
+    /// Fraction of the positions in which the candidate plays the same move as the reference.
+    pub move_agreement: f32,
+}
+
+/// Compares evaluators with a reference like rollouts, for example to track the quality of the
+/// neural nets across versions.
+pub struct BenchmarkEvaluator<R: Evaluator> {
+    reference: R,
+}
+
+impl<R: Evaluator> BenchmarkEvaluator<R> {
+    pub fn new(reference: R) -> Self {
+        Self { reference }
+    }
+
+    /// Evaluates all `positions` with `candidate` and the reference.
+    ///
+    /// The equities are compared for each position, the best moves for each position with its dice.
+    /// Panics if `positions` is empty.
+    pub fn report<C: Evaluator>(
+        &self,
+        candidate: &C,
+        positions: &[(Position, Dice)],
+    ) -> BenchmarkReport {
+        assert!(
+            !positions.is_empty(),
+            "Benchmark needs at least one position"
+        );
+        let mut equity_error = 0.0;
+        let mut agreements = 0;
+        for (position, dice) in positions {
+            equity_error += (candidate.equity(position) - self.reference.equity(position)).abs();
+            if candidate.best_position_by_equity(position, dice)
+                == self.reference.best_position_by_equity(position, dice)
+            {
+                agreements += 1;
+            }
+        }
+        let number_of_positions = positions.len();
+        BenchmarkReport {
+            number_of_positions,
+            mean_absolute_equity_error: equity_error / number_of_positions as f32,
+            move_agreement: agreements as f32 / number_of_positions as f32,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::benchmark::BenchmarkEvaluator;
+    use engine::dice::Dice;
+    use engine::evaluator::EvaluatorFake;
+    use engine::pos;
+    use engine::probabilities::Probabilities;
+
+    #[test]
+    fn biased_candidate_has_equity_error_and_plays_other_move() {
+        // Given
+        let pos = pos!(x 7:2; o 20:2);
+        // With 4-2 the legal moves are 7/3 7/5 and 7/1, with 6-5 the only legal move is 7/1 7/2.
+        let positions = [(pos, Dice::new(4, 2)), (pos, Dice::new(6, 5))];
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let bad_for_opponent: Probabilities = [0.1, 0.0, 0.0, 0.9, 0.0, 0.0].into();
+        let mut reference = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        reference.insert(
+            pos!(x 5:1, 3:1; o 20:2).sides_switched(),
+            bad_for_opponent.clone(),
+        );
+        // The candidate is 0.2 too optimistic and prefers 7/1.
+        let mut candidate = EvaluatorFake::with_default([0.6, 0.0, 0.0, 0.4, 0.0, 0.0].into());
+        candidate.insert(pos!(x 7:1, 1:1; o 20:2).sides_switched(), bad_for_opponent);
+        // When
+        let report = BenchmarkEvaluator::new(reference).report(&candidate, &positions);
+        // Then
+        assert_eq!(report.number_of_positions, 2);
+        assert!((report.mean_absolute_equity_error - 0.2).abs() < 0.0001);
+        assert_eq!(report.move_agreement, 0.5);
+    }
+}
@@ -1,8 +1,8 @@
-use engine::dice_gen::DiceGen;
+use engine::dice_gen::{DiceGen, FastrandDice};
 use engine::evaluator::Evaluator;
 use engine::position::GameState::{GameOver, Ongoing};
-use engine::position::STARTING;
-use engine::probabilities::ResultCounter;
+use engine::position::{Position, STARTING};
+use engine::probabilities::{Probabilities, ResultCounter};
 
 pub struct Duel<T: Evaluator, U: Evaluator> {
     evaluator1: T,
@@ -22,8 +22,16 @@ impl<T: Evaluator, U: Evaluator> Duel<T, U> {
     /// The two `Evaluator`s will play twice each against each other.
     /// Either `Evaluator` will start once and play with the same dice as vice versa.
     pub fn duel<V: DiceGen>(&self, dice_gen: &mut V) -> ResultCounter {
-        let mut pos1 = STARTING;
-        let mut pos2 = STARTING;
+        self.duel_from(&STARTING, dice_gen)
+    }
+
+    /// Like [Duel::duel], but both games start in `from` instead of the starting position.
+    ///
+    /// In the first game `evaluator1` is on roll in `from`, in the second game `evaluator2`.
+    /// The results are from the perspective of `evaluator1`.
+    pub fn duel_from<V: DiceGen>(&self, from: &Position, dice_gen: &mut V) -> ResultCounter {
+        let mut pos1 = *from;
+        let mut pos2 = *from;
         let mut iteration = 0;
         let mut pos1_finished = false;
         let mut pos2_finished = false;
@@ -76,4 +84,136 @@ impl<T: Evaluator, U: Evaluator> Duel<T, U> {
         debug_assert!(counter.sum() == 2, "Each duel should have two game results");
         counter
     }
+
+    /// How many points per game `evaluator1` wins more than `evaluator2` would in the same seat, when
+    /// both play `from` against each other in `duels` duels.
+    ///
+    /// With a strong reference as `evaluator2`, for example a rollout, this estimates how much
+    /// `evaluator1` loses against (nearly) perfect play. Negative values mean `evaluator1` is weaker.
+    pub fn ppg_difference<V: DiceGen>(
+        &self,
+        from: &Position,
+        duels: usize,
+        dice_gen: &mut V,
+    ) -> f32 {
+        let counter = (0..duels).fold(ResultCounter::default(), |counter, _| {
+            counter.combine(&self.duel_from(from, dice_gen))
+        });
+        Probabilities::from(&counter).equity()
+    }
+}
+
+/// Outcome of [run_match], from the perspective of the first evaluator.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MatchResult {
+    pub games: usize,
+    /// Points per game in money games without cube, gammons and backgammons count double and triple.
+    pub ppg: f32,
+    /// Fraction of the games won.
+    pub win_rate: f32,
+}
+
+/// Plays `games` money games without cube between `a` and `b`, starting from the starting position.
+///
+/// The dice are derived from `seed`, so the same seed gives the same result. `a` starts the games
+/// with an even index, `b` the others, to remove the advantage of the first move.
+pub fn run_match<A: Evaluator, B: Evaluator>(a: &A, b: &B, games: usize, seed: u64) -> MatchResult {
+    let mut dice_gen = FastrandDice::with_seed(seed);
+    let mut counter = ResultCounter::default();
+    for game in 0..games {
+        let mut pos = STARTING;
+        let mut a_on_turn = game % 2 == 0;
+        loop {
+            match pos.game_state() {
+                Ongoing => {
+                    let dice = dice_gen.roll();
+                    pos = if a_on_turn {
+                        a.best_position_by_equity(&pos, &dice)
+                    } else {
+                        b.best_position_by_equity(&pos, &dice)
+                    };
+                    a_on_turn = !a_on_turn;
+                }
+                GameOver(result) => {
+                    // The result is from the perspective of the player on turn, who has lost.
+                    counter.add(if a_on_turn { result } else { result.reverse() });
+                    break;
+                }
+            }
+        }
+    }
+    let probabilities = Probabilities::from(&counter);
+    MatchResult {
+        games,
+        ppg: probabilities.equity(),
+        win_rate: probabilities.win(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::duel::{Duel, run_match};
+    use engine::dice_gen::FastrandDice;
+    use engine::evaluator::Evaluator;
+    use engine::position::Position;
+    use engine::position::presets::mutual_holding_game;
+    use engine::probabilities::Probabilities;
+
+    /// Test double of known strength. It likes to be ahead in the pip count, so it hits whenever
+    /// it can. With `sign` of `-1.0` it's the opposite and never hits.
+    struct PipLead {
+        sign: f32,
+    }
+
+    impl Evaluator for PipLead {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let (x_pips, o_pips) = pos.pip_counts();
+            let lead = o_pips as f32 - x_pips as f32;
+            let win = (0.5 + self.sign * lead / 400.0).clamp(0.0, 1.0);
+            [win, 0.0, 0.0, 1.0 - win, 0.0, 0.0].into()
+        }
+    }
+
+    #[test]
+    fn ppg_difference_of_weaker_evaluator_is_negative() {
+        // Given
+        let strong = PipLead { sign: 1.0 };
+        let weak = PipLead { sign: -1.0 };
+        let pos = mutual_holding_game();
+        // When
+        let weak_vs_strong =
+            Duel::new(weak, strong).ppg_difference(&pos, 20, &mut FastrandDice::with_seed(0));
+        let strong_vs_weak = Duel::new(PipLead { sign: 1.0 }, PipLead { sign: -1.0 })
+            .ppg_difference(&pos, 20, &mut FastrandDice::with_seed(0));
+        // Then
+        assert!(weak_vs_strong < 0.0);
+        assert_eq!(strong_vs_weak, -weak_vs_strong);
+    }
+
+    #[test]
+    fn run_match_is_reproducible() {
+        let strong = PipLead { sign: 1.0 };
+        let weak = PipLead { sign: -1.0 };
+        let first = run_match(&strong, &weak, 20, 7);
+        let second = run_match(&strong, &weak, 20, 7);
+        assert_eq!(first, second);
+        assert_eq!(first.games, 20);
+        assert!(first.ppg > 0.0);
+    }
+
+    #[test]
+    fn run_match_of_same_evaluators_is_near_zero() {
+        let evaluator = PipLead { sign: 1.0 };
+        let result = run_match(&evaluator, &evaluator, 200, 0);
+        assert!(result.ppg.abs() < 0.3);
+        assert!((result.win_rate - 0.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn ppg_difference_of_same_evaluators_is_zero() {
+        let duel = Duel::new(PipLead { sign: 1.0 }, PipLead { sign: 1.0 });
+        let difference =
+            duel.ppg_difference(&mutual_holding_game(), 5, &mut FastrandDice::with_seed(0));
+        assert_eq!(difference, 0.0);
+    }
 }
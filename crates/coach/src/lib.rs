@@ -1,6 +1,8 @@
+pub mod benchmark;
 pub mod coach_helpers;
 pub mod data;
 pub mod duel;
 pub mod position_finder;
 pub mod rollout;
+pub mod self_play;
 pub mod unwrap;
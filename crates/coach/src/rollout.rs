@@ -1,9 +1,13 @@
-use engine::dice::{ALL_441, Dice};
-use engine::dice_gen::{DiceGen, FastrandDice};
-use engine::evaluator::{Evaluator, RandomEvaluator};
+use engine::dice::{ALL_21, Dice};
+use engine::dice_gen::{ComplementedDice, DiceGen, FastrandDice};
+use engine::evaluator::{Evaluator, EvaluatorInfo, PartialEvaluator, RandomEvaluator};
+use engine::position::GameResult::{LoseBg, LoseGammon, LoseNormal, WinBg, WinGammon, WinNormal};
 use engine::position::GameState::{GameOver, Ongoing};
-use engine::position::{GameResult, Position};
-use engine::probabilities::{Probabilities, ResultCounter};
+use engine::position::{GameResult, Player, Position};
+use engine::probabilities::{Probabilities, ResultCounter, cmp_nan_lowest};
+use logic::cube::{CubeAction, CubeInfo, CubePosition, CubeRolloutResult, CubeState, MatchState};
+use logic::game_context::GameContext;
+use logic::match_equity::position_equity;
 use rayon::prelude::*;
 
 /// Two `RolloutEvaluator`s which are initialized with the same `seed` and the same evaluators,
@@ -11,10 +15,264 @@ use rayon::prelude::*;
 pub struct RolloutEvaluator<T: Evaluator> {
     evaluator: T,
     seed: u64,
+    mode: RolloutMode,
+    antithetic: bool,
+    count_wastage: bool,
+    /// Ends single rollouts after this many random half moves with a static evaluation.
+    truncation: Option<usize>,
+    /// Ends single rollouts early, as soon as it can evaluate a position.
+    partial: Option<Box<dyn PartialEvaluator + Sync>>,
 }
 
-/// We will do 1296 single rollouts and we need different dice for them.
-/// Each of those 1296 rollouts will have a separate dice generator, here are the seeds to initialize them.
+/// Which first rolls are enumerated during a rollout. All later rolls are random.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RolloutMode {
+    /// All 1296 combinations of the first two rolls are rolled out once. This is the default
+    /// and the same as `Enumerated { plies: 2 }`.
+    #[default]
+    Full,
+    /// All combinations of the first `plies` rolls (1 to 3) are rolled out once, so 36, 1296 or
+    /// 46656 games. More plies take longer, but reduce the variance caused by the first rolls.
+    Enumerated { plies: u32 },
+    /// All 36 first rolls of the player on turn are rolled out `games_per_roll` times each,
+    /// the opponent's first roll is already random. This means 36 × `games_per_roll` games.
+    QuasiRandom { games_per_roll: usize },
+}
+
+impl RolloutMode {
+    /// Number of games of a rollout in this mode.
+    pub fn games(&self) -> usize {
+        match self {
+            RolloutMode::Full => 1296,
+            RolloutMode::Enumerated { plies } => 36_usize.pow(*plies),
+            RolloutMode::QuasiRandom { games_per_roll } => 36 * games_per_roll,
+        }
+    }
+
+    /// How many first rolls are enumerated and how often each combination of them is played.
+    fn plies_and_games_per_roll(&self) -> (u32, usize) {
+        match *self {
+            RolloutMode::Full => (2, 1),
+            RolloutMode::Enumerated { plies } => (plies, 1),
+            RolloutMode::QuasiRandom { games_per_roll } => (1, games_per_roll),
+        }
+    }
+}
+
+/// Statistics about the games of a rollout, from the perspective of the player on turn.
+///
+/// Rollouts are noisy, so two positions should only be ranked if their confidence intervals
+/// don't overlap.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RolloutStats {
+    /// Number of games rolled out.
+    pub games: usize,
+    /// Cubeless equity, averaged over all games.
+    pub equity: f32,
+    /// Standard error of `equity`.
+    pub std_error: f32,
+    /// How often either player didn't use the full roll during the bearoff, summed over all games.
+    ///
+    /// Only counted if enabled with [RolloutEvaluator::with_wastage_counting], otherwise `0`.
+    pub wastage_events: u32,
+}
+
+impl RolloutStats {
+    /// Derives the statistics from the averaged results of `games` finished games.
+    ///
+    /// The equity of a finished game is one of ±1, ±2 or ±3, so the probabilities are enough to
+    /// calculate the variance. This isn't true for games stopped early with fractional results.
+    pub fn new(probabilities: &Probabilities, games: usize) -> Self {
+        let p = probabilities;
+        let mean_of_squares = p.win_normal
+            + p.lose_normal
+            + 4.0 * (p.win_gammon + p.lose_gammon)
+            + 9.0 * (p.win_bg + p.lose_bg);
+        Self::with_mean_of_squares(p.equity(), mean_of_squares, games)
+    }
+
+    /// Like [RolloutStats::new], but the variance comes from the equities of the single games in
+    /// `results`, so it's also right for games stopped early by a [PartialEvaluator] or truncation.
+    ///
+    /// `probabilities` are the averaged results, possibly with switched sides.
+    fn from_results(probabilities: &Probabilities, results: &GameResults) -> Self {
+        Self::with_mean_of_squares(
+            probabilities.equity(),
+            results.mean_of_squared_equities(),
+            results.games() as usize,
+        )
+    }
+
+    fn with_mean_of_squares(equity: f32, mean_of_squares: f32, games: usize) -> Self {
+        let std_error = if games > 1 {
+            let n = games as f32;
+            // Sample variance, corrected with Bessel's factor `n / (n - 1)`.
+            let variance = (mean_of_squares - equity * equity).max(0.0) * n / (n - 1.0);
+            (variance / n).sqrt()
+        } else {
+            f32::INFINITY
+        };
+        Self {
+            games,
+            equity,
+            std_error,
+            wastage_events: 0,
+        }
+    }
+
+    /// Interval in which the true equity lies with a probability of about 95%.
+    pub fn confidence_interval(&self) -> (f32, f32) {
+        let margin = 1.96 * self.std_error;
+        (self.equity - margin, self.equity + margin)
+    }
+
+    /// `true` if the confidence intervals overlap, so the ranking of the two rollouts is not significant.
+    pub fn overlaps(&self, other: &RolloutStats) -> bool {
+        let (low, high) = self.confidence_interval();
+        let (other_low, other_high) = other.confidence_interval();
+        low <= other_high && other_low <= high
+    }
+
+    /// `true` if the equities of the two rollouts differ significantly at the 95% level.
+    ///
+    /// This is a two-sample test on the difference of the means, so it's less conservative
+    /// than [RolloutStats::overlaps]: two intervals may overlap slightly although the difference is significant.
+    pub fn difference_significant(&self, other: &RolloutStats) -> bool {
+        let std_error = self.std_error.hypot(other.std_error);
+        (self.equity - other.equity).abs() > 1.96 * std_error
+    }
+}
+
+/// Results of many single rollouts.
+///
+/// A finished game is counted exactly in `counter`. A game stopped early by a [PartialEvaluator]
+/// or by truncation adds its probabilities to `fractional` instead. Floating point addition isn't
+/// associative, so batches of games must be combined in a fixed order, see [combine_in_order].
+#[derive(Default)]
+struct GameResults {
+    counter: ResultCounter,
+    fractional: Probabilities,
+    fractional_games: u32,
+    /// Sum of the squared equities of the games with fractional results.
+    fractional_squares: f32,
+    wastage_events: u32,
+}
+
+impl GameResults {
+    fn add(&mut self, probabilities: Probabilities) {
+        self.add_results(probabilities, 1);
+    }
+
+    fn add_results(&mut self, probabilities: Probabilities, amount: u32) {
+        match finished_result(&probabilities) {
+            Some(result) => self.counter.add_results(result, amount),
+            None => {
+                let equity = probabilities.equity();
+                self.fractional_squares += equity * equity * amount as f32;
+                self.fractional =
+                    std::mem::take(&mut self.fractional) + probabilities * amount as f32;
+                self.fractional_games += amount;
+            }
+        }
+    }
+
+    fn combine(self, other: &GameResults) -> Self {
+        Self {
+            counter: self.counter.combine(&other.counter),
+            fractional: self.fractional + other.fractional.clone(),
+            fractional_games: self.fractional_games + other.fractional_games,
+            fractional_squares: self.fractional_squares + other.fractional_squares,
+            wastage_events: self.wastage_events + other.wastage_events,
+        }
+    }
+
+    fn games(&self) -> u32 {
+        self.counter.sum() + self.fractional_games
+    }
+
+    /// The squared cubeless equities of the single games, averaged over all games.
+    fn mean_of_squared_equities(&self) -> f32 {
+        let counted = |result: GameResult| self.counter.num_of(result) as f32;
+        let finished_squares = counted(WinNormal)
+            + counted(LoseNormal)
+            + 4.0 * (counted(WinGammon) + counted(LoseGammon))
+            + 9.0 * (counted(WinBg) + counted(LoseBg));
+        (finished_squares + self.fractional_squares) / self.games() as f32
+    }
+
+    /// The probabilities averaged over all games.
+    fn average(&self) -> Probabilities {
+        let games = self.games() as f32;
+        let counted = |result: GameResult| self.counter.num_of(result) as f32;
+        let p = &self.fractional;
+        Probabilities {
+            win_normal: (counted(WinNormal) + p.win_normal) / games,
+            win_gammon: (counted(WinGammon) + p.win_gammon) / games,
+            win_bg: (counted(WinBg) + p.win_bg) / games,
+            lose_normal: (counted(LoseNormal) + p.lose_normal) / games,
+            lose_gammon: (counted(LoseGammon) + p.lose_gammon) / games,
+            lose_bg: (counted(LoseBg) + p.lose_bg) / games,
+        }
+    }
+}
+
+/// The result of a finished game, `None` if `probabilities` are fractional.
+fn finished_result(probabilities: &Probabilities) -> Option<GameResult> {
+    let p = probabilities;
+    [
+        (p.win_normal, WinNormal),
+        (p.win_gammon, WinGammon),
+        (p.win_bg, WinBg),
+        (p.lose_normal, LoseNormal),
+        (p.lose_gammon, LoseGammon),
+        (p.lose_bg, LoseBg),
+    ]
+    .into_iter()
+    .find(|(value, _)| *value == 1.0)
+    .map(|(_, result)| result)
+}
+
+/// Combines batches of games one after another in the order of `results`.
+///
+/// Rayon's `reduce` would combine them in an order depending on the work stealing, then
+/// fractional results could differ in the last bits from one rollout to the next.
+fn combine_in_order(results: Vec<GameResults>) -> GameResults {
+    results
+        .iter()
+        .fold(GameResults::default(), |combined, batch| {
+            combined.combine(batch)
+        })
+}
+
+/// `false` if the player on turn can't double anyway, then the cube decision needs no evaluation.
+fn may_double(ctx: &GameContext) -> bool {
+    ctx.cube.position != CubePosition::OpponentOwned
+        && !matches!(ctx.match_state, MatchState::Match { crawford: true, .. })
+}
+
+/// The score of a game for `x` at the score of `ctx`, played for the cube value of `cube`.
+///
+/// `result` is from the perspective of `x`. In a money game the score is in points, in match play
+/// it's the match winning chances after the game.
+fn match_score(ctx: &GameContext, result: &Probabilities, cube: CubeState) -> f32 {
+    match ctx.match_state {
+        MatchState::Money => cube.value() as f32 * result.equity(),
+        MatchState::Match { x_away, o_away, .. } => {
+            position_equity(result, x_away, o_away, cube.value())
+        }
+    }
+}
+
+/// `true` if `x` bears off in `before` and the pip count drops by less than the pips of `dice`.
+///
+/// `after` is the position after the move of `x`, already with switched sides.
+fn is_wastage(before: &Position, after: &Position, dice: &Dice) -> bool {
+    before.distance_to_bearoff(Player::X) == 0
+        && before.pip_count_for(Player::X) - after.pip_count_for(Player::O) < dice.pips()
+}
+
+/// We will do many single rollouts (typically 1296) and we need different dice for them.
+/// Each of those rollouts will have a separate dice generator, here are the seeds to initialize them.
 fn dice_seeds(dice_gen: &mut FastrandDice, amount: usize) -> Vec<u64> {
     let mut seeds = Vec::with_capacity(amount);
     for _ in 0..amount {
@@ -23,25 +281,341 @@ fn dice_seeds(dice_gen: &mut FastrandDice, amount: usize) -> Vec<u64> {
     seeds
 }
 
+/// All combinations of the first `plies` rolls, together with how often they appear in `36^plies` rolls.
+///
+/// For two plies, this is the same as [engine::dice::ALL_441], in the same order.
+fn enumerated_dice(plies: u32) -> Vec<(Vec<Dice>, usize)> {
+    let mut combinations = vec![(Vec::new(), 1)];
+    for _ in 0..plies {
+        combinations = combinations
+            .into_iter()
+            .flat_map(|(first, amount)| {
+                ALL_21.iter().map(move |(dice, dice_amount)| {
+                    let mut next = first.clone();
+                    next.push(*dice);
+                    (next, amount * dice_amount)
+                })
+            })
+            .collect();
+    }
+    combinations
+}
+
 impl<T: Evaluator + Sync> Evaluator for RolloutEvaluator<T> {
-    /// Rolls out 1296 times, the dice for the first two half moves are given, rest is random
+    /// Rolls out 1296 times, the dice for the first two half moves are given, rest is random.
+    ///
+    /// Other modes can be chosen with [RolloutEvaluator::with_mode].
     fn eval(&self, pos: &Position) -> Probabilities {
         debug_assert!(pos.game_state() == Ongoing);
 
-        let mut dice_gen = FastrandDice::with_seed(self.seed);
-        let dice_and_seeds =
-            ALL_441.map(|(dice, amount)| (dice, dice_seeds(&mut dice_gen, amount)));
-        let game_results: ResultCounter = dice_and_seeds
-            .par_iter()
-            .map(|(dice, seeds)| self.results_from_single_rollouts(pos, dice, seeds))
-            .reduce(ResultCounter::default, |a, b| a.combine(&b));
+        let game_results = combine_in_order(
+            self.dice_and_seeds()
+                .par_iter()
+                .map(|(dice, seeds)| self.results_from_single_rollouts(pos, dice, seeds))
+                .collect(),
+        );
 
         debug_assert_eq!(
-            game_results.sum(),
-            6 * 6 * 6 * 6,
-            "Rollout should look at 1296 games"
+            game_results.games() as usize,
+            self.mode.games(),
+            "Rollout should look at all games of the mode"
         );
-        Probabilities::from(&game_results)
+        game_results.average()
+    }
+
+    /// Strength of a rollout with the wrapped evaluator, which decides the moves.
+    fn info(&self) -> EvaluatorInfo {
+        EvaluatorInfo {
+            name: "Rollout",
+            is_rollout: true,
+            ..self.evaluator.info()
+        }
+    }
+}
+
+/// A single game of a rollout: the dice of each half move and the position after the move.
+///
+/// The positions are from the perspective of the player on turn at the root of the rollout.
+pub type GameTrace = Vec<(Dice, Position)>;
+
+/// Move hook for [RolloutEvaluator::eval_with_move_hook] which always plays the best move by equity,
+/// so the rollout is the same as `eval`.
+pub fn greedy_move(_pos: &Position, _dice: &Dice, _candidates: &[Position]) -> usize {
+    0
+}
+
+/// No real game needs this many half moves. If a single rollout doesn't end before, something is
+/// wrong, for example both players are closed out and can't ever move again.
+const MAX_HALF_MOVES: usize = 10_000;
+
+/// Number of combinations of first rolls which `eval_stream` rolls out before it yields
+/// the next running estimate.
+const STREAM_BATCH_SIZE: usize = 21;
+
+impl<T: Evaluator + Sync> RolloutEvaluator<T> {
+    /// Does the same rollout as `eval`, but yields the running estimate after each batch of games.
+    ///
+    /// Each item is the number of games rolled out so far together with the probabilities
+    /// from those games. The last item is identical to the result of `eval`.
+    /// Dropping the iterator stops the rollout.
+    pub fn eval_stream(&self, pos: &Position) -> impl Iterator<Item = (usize, Probabilities)> {
+        debug_assert!(pos.game_state() == Ongoing);
+
+        let pos = *pos;
+        let dice_and_seeds = self.dice_and_seeds();
+        let mut game_results = GameResults::default();
+        (0..dice_and_seeds.len())
+            .step_by(STREAM_BATCH_SIZE)
+            .map(move |start| {
+                let end = (start + STREAM_BATCH_SIZE).min(dice_and_seeds.len());
+                let batch_results = combine_in_order(
+                    dice_and_seeds[start..end]
+                        .par_iter()
+                        .map(|(dice, seeds)| self.results_from_single_rollouts(&pos, dice, seeds))
+                        .collect(),
+                );
+                game_results = std::mem::take(&mut game_results).combine(&batch_results);
+                (game_results.games() as usize, game_results.average())
+            })
+    }
+
+    /// Does the same rollout as `eval`, but additionally returns the traces of `sample_count`
+    /// randomly chosen games of it, for example to see how a rollout reached a backgammon.
+    ///
+    /// The games are chosen deterministically from the seed and are sorted like in the rollout.
+    /// Each trace ends with the game over, or when the partial evaluator could evaluate the position.
+    pub fn eval_with_samples(
+        &self,
+        pos: &Position,
+        sample_count: usize,
+    ) -> (Probabilities, Vec<GameTrace>) {
+        let probabilities = self.eval(pos);
+        let dice_and_seeds = self.dice_and_seeds();
+        let games = dice_and_seeds
+            .iter()
+            .enumerate()
+            .flat_map(|(combination, (_, seeds))| (0..seeds.len()).map(move |i| (combination, i)));
+        let mut chosen = fastrand::Rng::with_seed(self.seed).choose_multiple(games, sample_count);
+        chosen.sort_unstable();
+        let traces = chosen
+            .into_iter()
+            .map(|(combination, index)| {
+                let (first_dice, seeds) = &dice_and_seeds[combination];
+                if self.antithetic && index % 2 == 1 {
+                    let mut dice_gen =
+                        ComplementedDice::new(FastrandDice::with_seed(seeds[index - 1]));
+                    self.trace_with_generator(pos, first_dice, &mut dice_gen)
+                } else {
+                    let mut dice_gen = FastrandDice::with_seed(seeds[index]);
+                    self.trace_with_generator(pos, first_dice, &mut dice_gen)
+                }
+            })
+            .collect();
+        (probabilities, traces)
+    }
+
+    /// Rolls out `pos` with the cube and at the score of `ctx`. Returns the match winning chances
+    /// of `x` in match play and the cubeful equity of `x` in points in a money game.
+    ///
+    /// Before each roll the player on turn decides about the cube with [CubeInfo::for_state], in
+    /// match play with the match equity table. A passed double ends the game, a taken double turns
+    /// the cube. Each game is scored at its final cube value. Both players choose their moves by
+    /// [GameContext::value] from their own perspective, so gammons count as much as they are worth
+    /// at this score and cube.
+    ///
+    /// The games use the same enumerated rolls and seeds as `eval`, also for antithetic sampling.
+    pub fn eval_match(&self, pos: &Position, ctx: &GameContext) -> f32 {
+        self.rollout_match(pos, ctx, true)
+    }
+
+    /// Rolls out the three cube actions of `x` in `pos` with the cube and at the score of `ctx`,
+    /// in the units of [RolloutEvaluator::eval_match]. Returns an error if `x` may not double.
+    ///
+    /// In a money game the opponent beavers a double if [CubeInfo::should_beaver] says so. Then
+    /// the equity after the take is the one of four times the stake, with the opponent owning the
+    /// cube. All later cube decisions are the same as in [RolloutEvaluator::eval_match].
+    pub fn rollout_cube(
+        &self,
+        pos: &Position,
+        ctx: &GameContext,
+    ) -> Result<CubeRolloutResult, &'static str> {
+        let doubled = ctx.cube.doubled()?;
+        let cube_info = CubeInfo::for_state(&self.evaluator.eval(pos), ctx.cube, ctx.match_state);
+        let taken = if cube_info.should_beaver() {
+            // The opponent takes, redoubles and keeps the cube.
+            CubeState {
+                level: doubled
+                    .level
+                    .checked_add(1)
+                    .ok_or("the cube can't be turned any further")?,
+                ..doubled
+            }
+        } else {
+            doubled
+        };
+        Ok(CubeRolloutResult {
+            equity_no_double: self.rollout_match(pos, ctx, false),
+            equity_double_take: self.rollout_match(
+                pos,
+                &GameContext::new(taken, ctx.match_state),
+                false,
+            ),
+            equity_double_pass: match_score(ctx, &Probabilities::from(WinNormal), ctx.cube),
+        })
+    }
+
+    /// Like [RolloutEvaluator::eval_match], but without a cube decision before the first roll if
+    /// `cube_at_root` is `false`.
+    fn rollout_match(&self, pos: &Position, ctx: &GameContext, cube_at_root: bool) -> f32 {
+        debug_assert!(pos.game_state() == Ongoing);
+
+        // The same for all games, so the root is evaluated only once.
+        let probabilities = (cube_at_root && may_double(ctx)).then(|| self.evaluator.eval(pos));
+        let scores: Vec<Vec<f32>> = self
+            .dice_and_seeds()
+            .par_iter()
+            .map(|(dice, seeds)| {
+                (0..seeds.len())
+                    .map(|index| {
+                        self.match_rollout_with_seeds(
+                            pos,
+                            probabilities.as_ref(),
+                            ctx,
+                            dice,
+                            seeds,
+                            index,
+                        )
+                    })
+                    .collect()
+            })
+            .collect();
+        // Summed up in order, like `combine_in_order`.
+        scores.iter().flatten().sum::<f32>() / self.mode.games() as f32
+    }
+
+    /// Plays the game with number `index` of [RolloutEvaluator::eval_match], the dice after
+    /// `first_dice` are seeded from `seeds` like in [RolloutEvaluator::single_rollout_with_seeds].
+    fn match_rollout_with_seeds(
+        &self,
+        from: &Position,
+        probabilities: Option<&Probabilities>,
+        ctx: &GameContext,
+        first_dice: &[Dice],
+        seeds: &[u64],
+        index: usize,
+    ) -> f32 {
+        if self.antithetic && index % 2 == 1 {
+            let mut dice_gen = ComplementedDice::new(FastrandDice::with_seed(seeds[index - 1]));
+            self.single_match_rollout(from, probabilities, ctx, first_dice, &mut dice_gen)
+        } else {
+            let mut dice_gen = FastrandDice::with_seed(seeds[index]);
+            self.single_match_rollout(from, probabilities, ctx, first_dice, &mut dice_gen)
+        }
+    }
+
+    /// The score of a single game of [RolloutEvaluator::eval_match] for `x`, see [match_score].
+    ///
+    /// `probabilities` are the evaluation of `from` for the cube decision before the first roll,
+    /// `None` if there is no cube decision then.
+    fn single_match_rollout<U: DiceGen>(
+        &self,
+        from: &Position,
+        probabilities: Option<&Probabilities>,
+        ctx: &GameContext,
+        first_dice: &[Dice],
+        dice_gen: &mut U,
+    ) -> f32 {
+        let mut player_on_turn = true;
+        let mut pos = *from;
+        let mut probabilities = probabilities.cloned();
+        // Always from the perspective of the player on turn, like `pos` and `probabilities`.
+        let mut on_turn = *ctx;
+        let enumerated = first_dice.len();
+        let mut first_dice = first_dice.iter();
+        for half_moves in 1..=MAX_HALF_MOVES {
+            if (half_moves > 1 || probabilities.is_some()) && may_double(&on_turn) {
+                let probabilities = probabilities.get_or_insert_with(|| self.evaluator.eval(&pos));
+                match CubeInfo::for_state(probabilities, on_turn.cube, on_turn.match_state)
+                    .cube_action()
+                {
+                    CubeAction::DoublePass => {
+                        // The player on turn cashes the current stake.
+                        let cashed = Probabilities::from(WinNormal);
+                        let cashed = if player_on_turn {
+                            cashed
+                        } else {
+                            cashed.switch_sides()
+                        };
+                        return match_score(ctx, &cashed, on_turn.cube);
+                    }
+                    CubeAction::DoubleTake => {
+                        if let Ok(cube) = on_turn.cube.doubled() {
+                            on_turn.cube = cube;
+                        }
+                    }
+                    CubeAction::NoDouble | CubeAction::TooGoodToDouble => {}
+                }
+            }
+            let dice = first_dice
+                .next()
+                .copied()
+                .unwrap_or_else(|| dice_gen.roll());
+            let (next, next_probabilities) =
+                self.best_position_and_probabilities(&pos, &dice, on_turn.value());
+            pos = next;
+            if let Some(result) =
+                self.stop_or_truncate_at(&pos, half_moves.saturating_sub(enumerated))
+            {
+                let result = if player_on_turn {
+                    result.switch_sides()
+                } else {
+                    result
+                };
+                return match_score(ctx, &result, on_turn.cube);
+            }
+            probabilities = next_probabilities;
+            player_on_turn = !player_on_turn;
+            on_turn = on_turn.sides_switched();
+        }
+        panic!(
+            "Rollout didn't end after {MAX_HALF_MOVES} half moves, position {}",
+            pos.position_id()
+        );
+    }
+
+    /// The best move by `value`, like [Evaluator::best_position], so it has already switched sides.
+    ///
+    /// Also returns the probabilities of the position after the move, from the perspective of the
+    /// opponent who is on turn then. Forced moves and moves which end the game aren't evaluated,
+    /// their probabilities are `None`.
+    fn best_position_and_probabilities<F>(
+        &self,
+        pos: &Position,
+        dice: &Dice,
+        value: F,
+    ) -> (Position, Option<Probabilities>)
+    where
+        F: Fn(&Probabilities) -> f32,
+    {
+        let positions = pos.all_positions_after_moving(dice);
+        if positions.len() == 1 {
+            return (positions[0], None);
+        }
+        if let Some(finished) = positions.iter().find(|position| position.has_lost()) {
+            return (*finished, None);
+        }
+        self.evaluator
+            .eval_batch(positions)
+            .into_iter()
+            .map(|(position, probabilities)| {
+                let value = value(&probabilities.switch_sides());
+                (position, probabilities, value)
+            })
+            // Reversed, so that the first of equally good moves is chosen, like by the evaluator.
+            // A `NaN` value is never chosen, unless all values are `NaN`.
+            .min_by(|(_, _, a), (_, _, b)| cmp_nan_lowest(*b, *a))
+            .map(|(position, probabilities, _)| (position, Some(probabilities)))
+            .expect("There is always at least one legal move.")
     }
 }
 
@@ -58,7 +632,315 @@ impl<T: Evaluator> RolloutEvaluator<T> {
     }
 
     pub fn with_evaluator_and_seed(evaluator: T, seed: u64) -> Self {
-        Self { evaluator, seed }
+        Self {
+            evaluator,
+            seed,
+            mode: RolloutMode::default(),
+            antithetic: false,
+            count_wastage: false,
+            truncation: None,
+            partial: None,
+        }
+    }
+
+    /// A quick rollout preset, balancing speed and quality for a decent evaluation in one line.
+    ///
+    /// Plays 576 games instead of 1296: each of the 36 first rolls 16 times, see
+    /// [RolloutMode::QuasiRandom]. The games are played in antithetic pairs, see
+    /// [RolloutEvaluator::with_antithetic], which makes up for part of the variance of fewer games.
+    /// Each game is truncated after 10 random half moves and then scored with a static evaluation,
+    /// see [RolloutEvaluator::with_truncation].
+    ///
+    /// The tradeoff: much faster than a full rollout, but the result inherits the bias of
+    /// `evaluator` in all games which are still going on after the truncation. Use a full rollout
+    /// where the last bit of accuracy matters, for example to create training data.
+    pub fn quick(evaluator: T) -> Self {
+        Self::with_evaluator(evaluator)
+            .with_mode(RolloutMode::QuasiRandom { games_per_roll: 16 })
+            .with_antithetic(true)
+            .with_truncation(10)
+    }
+
+    /// Deterministic like [RolloutEvaluator::with_evaluator], but each single rollout stops as soon
+    /// as `partial` can evaluate the position, for example with a [BearoffDatabase](engine::bearoff::BearoffDatabase).
+    ///
+    /// The game then counts with the probabilities of `partial` instead of a single result. Until then,
+    /// `fallback_during_play` chooses the moves.
+    pub fn new_partial<P: PartialEvaluator + Sync + 'static>(
+        partial: P,
+        fallback_during_play: T,
+    ) -> Self {
+        Self {
+            partial: Some(Box::new(partial)),
+            ..Self::with_evaluator(fallback_during_play)
+        }
+    }
+
+    /// Changes which first rolls are enumerated, see [RolloutMode].
+    ///
+    /// Panics if `Enumerated` has not 1, 2 or 3 plies or `QuasiRandom` has no games per roll.
+    pub fn with_mode(self, mode: RolloutMode) -> Self {
+        match mode {
+            RolloutMode::Full => {}
+            RolloutMode::Enumerated { plies } => assert!(
+                (1..=3).contains(&plies),
+                "Only 1, 2 or 3 plies can be enumerated."
+            ),
+            RolloutMode::QuasiRandom { games_per_roll } => assert!(
+                games_per_roll > 0,
+                "Quasi random rollouts need at least one game per roll."
+            ),
+        }
+        Self { mode, ..self }
+    }
+
+    /// Plays the random games in pairs: the second game of each pair rolls the complements
+    /// `7 - d` of the dice of the first game.
+    ///
+    /// Big and small rolls cancel each other out within a pair, which reduces the variance of the
+    /// result at the same number of games. Only the random dice are complemented, not the enumerated
+    /// first rolls. The number of games doesn't change; if it's odd, the last game has no partner.
+    pub fn with_antithetic(self, antithetic: bool) -> Self {
+        Self { antithetic, ..self }
+    }
+
+    /// Counts the wastage events in the random games of [RolloutEvaluator::rollout_moves], see
+    /// [RolloutStats::wastage_events].
+    ///
+    /// A wastage event is a half move in the bearoff in which the pip count drops by less than the
+    /// rolled pips: a die is used to bear off a checker from a lower point, or can't be played at all.
+    /// Many of them point to gaps or stacks in the bearoff structure.
+    pub fn with_wastage_counting(self, count_wastage: bool) -> Self {
+        Self {
+            count_wastage,
+            ..self
+        }
+    }
+
+    /// Stops each single rollout after `half_moves` random half moves, not counting the enumerated
+    /// first rolls, and counts the game with the evaluation of the reached position.
+    ///
+    /// Truncated games are much faster, but the result is only as good as the evaluator in the
+    /// reached positions. Finished games and the partial evaluator still end a rollout earlier.
+    /// Panics if `half_moves` is `0`.
+    pub fn with_truncation(self, half_moves: usize) -> Self {
+        assert!(
+            half_moves > 0,
+            "Rollouts need at least one random half move."
+        );
+        Self {
+            truncation: Some(half_moves),
+            ..self
+        }
+    }
+
+    /// Enumerates the first `plies` rolls exactly before the dice become random,
+    /// see [RolloutMode::Enumerated]. Panics if `plies` is not 1, 2 or 3.
+    pub fn with_enumerated_plies(self, plies: u32) -> Self {
+        self.with_mode(RolloutMode::Enumerated { plies })
+    }
+
+    /// Does the same rollout as `eval`, but `hook` chooses each move instead of the evaluator.
+    ///
+    /// For each decision `hook` gets the position, the dice and all legal moves, and returns the
+    /// index of the move to play. The moves are sorted like the evaluator would choose them, the best
+    /// by equity first, and have switched sides like in [Position::all_positions_after_moving].
+    /// With [greedy_move] the result is the same as `eval`. This allows counterfactual rollouts,
+    /// for example "what if I never leave a blot". The games are played one after another.
+    /// Panics if `hook` returns an index outside of the moves.
+    pub fn eval_with_move_hook<H>(&self, pos: &Position, hook: H) -> Probabilities
+    where
+        H: Fn(&Position, &Dice, &[Position]) -> usize,
+    {
+        debug_assert!(pos.game_state() == Ongoing);
+
+        let mut game_results = GameResults::default();
+        for (first_dice, seeds) in self.dice_and_seeds() {
+            for index in 0..seeds.len() {
+                let result = if self.antithetic && index % 2 == 1 {
+                    let mut dice_gen =
+                        ComplementedDice::new(FastrandDice::with_seed(seeds[index - 1]));
+                    self.single_rollout_with_hook(pos, &first_dice, &mut dice_gen, &hook)
+                } else {
+                    let mut dice_gen = FastrandDice::with_seed(seeds[index]);
+                    self.single_rollout_with_hook(pos, &first_dice, &mut dice_gen, &hook)
+                };
+                game_results.add(result);
+            }
+        }
+        game_results.average()
+    }
+
+    /// Does the same rollout as `eval`, but all random rolls come from `dice_gen`.
+    ///
+    /// The first rolls are enumerated like in `eval`, only the rolls after them are taken from
+    /// `dice_gen`. The games are played one after another, so a [DiceGenMock](engine::dice_gen::DiceGenMock)
+    /// can script a whole rollout and the result is reproducible with any [DiceGen].
+    pub fn eval_with_dice<D: DiceGen>(&self, pos: &Position, dice_gen: &mut D) -> Probabilities {
+        debug_assert!(pos.game_state() == Ongoing);
+
+        let (plies, games_per_roll) = self.mode.plies_and_games_per_roll();
+        let mut game_results = GameResults::default();
+        for (first_dice, amount) in enumerated_dice(plies) {
+            let games = amount * games_per_roll;
+            match self.single_rollout_with_dice(pos, &first_dice) {
+                Ok(result) => game_results.add_results(result, games as u32),
+                Err(after_first_dice) => {
+                    for _ in 0..games {
+                        let result =
+                            self.single_rollout_with_generator(&after_first_dice, dice_gen);
+                        // After an odd number of half moves, the position is from the opponent's perspective.
+                        if first_dice.len().is_multiple_of(2) {
+                            game_results.add(result);
+                        } else {
+                            game_results.add(result.switch_sides());
+                        }
+                    }
+                }
+            }
+        }
+        game_results.average()
+    }
+
+    /// Rolls out `pos` after moving with `dice` to `chosen`, instead of the best move of the evaluator.
+    ///
+    /// Only the root move is fixed, all later moves are the best moves by equity. Like the positions
+    /// returned by [Position::all_positions_after_moving], `chosen` has already switched sides.
+    /// The returned probabilities are from the perspective of player `x` who moves with `dice`.
+    /// Returns an error if `chosen` can't be reached from `pos` with `dice`.
+    ///
+    /// The games use the same enumerated rolls and seeds as `eval` of `pos`, only the first move
+    /// is replaced by `chosen`. So if `chosen` is the move the evaluator plays with every roll,
+    /// the result is identical to `eval(pos)`.
+    pub fn eval_after_move(
+        &self,
+        pos: &Position,
+        dice: &Dice,
+        chosen: &Position,
+    ) -> Result<Probabilities, &'static str>
+    where
+        T: Sync,
+    {
+        if !pos.all_positions_after_moving(dice).contains(chosen) {
+            return Err("The chosen position is not a legal move with these dice.");
+        }
+        let probabilities = match chosen.game_state() {
+            GameOver(result) => Probabilities::from(result),
+            Ongoing => combine_in_order(
+                self.dice_and_seeds()
+                    .par_iter()
+                    // The first enumerated roll is replaced by the move to `chosen`.
+                    .map(|(dice, seeds)| {
+                        self.results_from_single_rollouts(chosen, &dice[1..], seeds)
+                    })
+                    .collect(),
+            )
+            .average(),
+        };
+        Ok(probabilities.switch_sides())
+    }
+
+    /// Rolls out all legal moves for the given position and dice, `games_per_move` games each.
+    ///
+    /// Sorted, the best move by rolled out equity is first in the vector.
+    /// The positions and probabilities are from the perspective of player `x` who has to move,
+    /// like in [Evaluator::positions_and_probabilities_by_equity].
+    /// Unlike `eval`, all dice are random.
+    pub fn rollout_moves(
+        &self,
+        pos: &Position,
+        dice: &Dice,
+        games_per_move: usize,
+    ) -> Vec<(Position, Probabilities, RolloutStats)>
+    where
+        T: Sync,
+    {
+        let mut dice_gen = FastrandDice::with_seed(self.seed);
+        let seeds = dice_seeds(&mut dice_gen, games_per_move);
+        let mut results: Vec<(Position, Probabilities, RolloutStats)> = pos
+            .all_positions_after_moving(dice)
+            .into_iter()
+            .map(|after_move| {
+                let game_results = self.results_from_random_rollouts(&after_move, &seeds);
+                let probabilities = game_results.average().switch_sides();
+                let stats = RolloutStats {
+                    wastage_events: game_results.wastage_events,
+                    ..RolloutStats::from_results(&probabilities, &game_results)
+                };
+                (after_move.sides_switched(), probabilities, stats)
+            })
+            .collect();
+        results.sort_by(|(_, _, a), (_, _, b)| cmp_nan_lowest(b.equity, a.equity));
+        results
+    }
+
+    /// Will do *n* rollouts from the given position with random dice, with *n* being the length of `seeds`.
+    fn results_from_random_rollouts(&self, from: &Position, seeds: &[u64]) -> GameResults
+    where
+        T: Sync,
+    {
+        let mut counter = GameResults::default();
+        if let Some(probabilities) = self.stop_at(from) {
+            counter.add_results(probabilities, seeds.len() as u32);
+            return counter;
+        }
+        combine_in_order(
+            (0..seeds.len())
+                .into_par_iter()
+                .map(|index| {
+                    let mut counter = GameResults::default();
+                    let (result, wastage_events) =
+                        self.single_rollout_with_seeds(from, seeds, index);
+                    counter.add(result);
+                    counter.wastage_events = wastage_events;
+                    counter
+                })
+                .collect(),
+        )
+    }
+
+    /// Plays a single game with scripted dice, for example to reproduce a game exactly.
+    ///
+    /// Both players use the moves the evaluator thinks are best by equity. Returns all positions
+    /// from `from` until the game is over, all of them from the perspective of the player on turn
+    /// in `from`. Dice which are left over after the game ended are ignored.
+    pub fn trace_single(
+        &self,
+        from: &Position,
+        dice: &[(usize, usize)],
+    ) -> Result<Vec<Position>, &'static str> {
+        let mut positions = vec![*from];
+        let mut player_on_turn = true;
+        let mut pos = *from;
+        let mut dice = dice.iter();
+        while !pos.is_game_over() {
+            let &(die1, die2) = dice
+                .next()
+                .ok_or("The dice ran out before the game was over.")?;
+            let dice = Dice::try_from((die1, die2))?;
+            pos = self.evaluator.best_position_by_equity(&pos, &dice);
+            player_on_turn = !player_on_turn;
+            if player_on_turn {
+                positions.push(pos);
+            } else {
+                positions.push(pos.sides_switched());
+            }
+        }
+        Ok(positions)
+    }
+
+    /// The enumerated first rolls for all games of a rollout, together with the seeds for the dice
+    /// generators of the remaining rolls. Derived from `self.seed`, so it's the same for each rollout.
+    fn dice_and_seeds(&self) -> Vec<(Vec<Dice>, Vec<u64>)> {
+        let mut dice_gen = FastrandDice::with_seed(self.seed);
+        let (plies, games_per_roll) = self.mode.plies_and_games_per_roll();
+        enumerated_dice(plies)
+            .into_iter()
+            .map(|(dice, amount)| {
+                let seeds = dice_seeds(&mut dice_gen, amount * games_per_roll);
+                (dice, seeds)
+            })
+            .collect()
     }
 
     /// Will do *n* rollouts from the given position, with *n* being the length of `seeds`.
@@ -68,18 +950,23 @@ impl<T: Evaluator> RolloutEvaluator<T> {
     fn results_from_single_rollouts(
         &self,
         from: &Position,
-        first_dice: &[Dice; 2],
+        first_dice: &[Dice],
         seeds: &[u64],
-    ) -> ResultCounter {
-        let mut counter = ResultCounter::default();
+    ) -> GameResults {
+        let mut counter = GameResults::default();
         match self.single_rollout_with_dice(from, first_dice) {
             Ok(result) => {
                 counter.add_results(result, seeds.len() as u32);
             }
-            Err(pos) => seeds.iter().for_each(|seed| {
-                let mut dice_gen = FastrandDice::with_seed(*seed);
-                let result = self.single_rollout_with_generator(&pos, &mut dice_gen);
-                counter.add(result);
+            Err(pos) => (0..seeds.len()).for_each(|index| {
+                let (result, wastage_events) = self.single_rollout_with_seeds(&pos, seeds, index);
+                counter.wastage_events += wastage_events;
+                // After an odd number of half moves, `pos` is from the opponent's perspective.
+                if first_dice.len().is_multiple_of(2) {
+                    counter.add(result);
+                } else {
+                    counter.add(result.switch_sides());
+                }
             }),
         }
         counter
@@ -87,22 +974,26 @@ impl<T: Evaluator> RolloutEvaluator<T> {
 
     /// Will try to do a rollout with the given `first_dice`.
     ///
-    /// If the game ends after using `first_dice` it will return the game result as `success`.
+    /// If the game ends (or the partial evaluator can evaluate it) after using `first_dice`, it will
+    /// return the probabilities of the result as `success`.
     /// If the game has not ended yet, it will return the then reached position as `failure`.
     fn single_rollout_with_dice(
         &self,
         from: &Position,
-        first_dice: &[Dice; 2],
-    ) -> Result<GameResult, Position> {
+        first_dice: &[Dice],
+    ) -> Result<Probabilities, Position> {
         let mut player_on_turn = true;
         let mut pos = *from;
+        let mut buffer = Vec::new();
         for dice in first_dice {
-            pos = self.evaluator.best_position_by_equity(&pos, dice);
-            if let GameOver(result) = pos.game_state() {
+            pos = self
+                .evaluator
+                .best_position_by_equity_reusing(&pos, dice, &mut buffer);
+            if let Some(probabilities) = self.stop_at(&pos) {
                 return if player_on_turn {
-                    Ok(result.reverse())
+                    Ok(probabilities.switch_sides())
                 } else {
-                    Ok(result)
+                    Ok(probabilities)
                 };
             }
             player_on_turn = !player_on_turn;
@@ -110,33 +1001,214 @@ impl<T: Evaluator> RolloutEvaluator<T> {
         Err(pos)
     }
 
+    /// Plays the game with number `index` with random dice, seeded from `seeds`.
+    ///
+    /// With antithetic sampling, each game with an odd `index` rolls the complements of the dice
+    /// of the game before.
+    fn single_rollout_with_seeds(
+        &self,
+        from: &Position,
+        seeds: &[u64],
+        index: usize,
+    ) -> (Probabilities, u32) {
+        if self.antithetic && index % 2 == 1 {
+            let mut dice_gen = ComplementedDice::new(FastrandDice::with_seed(seeds[index - 1]));
+            self.single_rollout_counting_wastage(from, &mut dice_gen)
+        } else {
+            let mut dice_gen = FastrandDice::with_seed(seeds[index]);
+            self.single_rollout_counting_wastage(from, &mut dice_gen)
+        }
+    }
+
+    /// Probabilities of the result of a single game with random dice, from the perspective of `x`.
+    ///
+    /// For a finished game these are `1` for the result and `0` for everything else.
     fn single_rollout_with_generator<U: DiceGen>(
         &self,
         from: &Position,
         dice_gen: &mut U,
-    ) -> GameResult {
+    ) -> Probabilities {
+        self.single_rollout_counting_wastage(from, dice_gen).0
+    }
+
+    /// Like [RolloutEvaluator::single_rollout_with_generator], but also returns the number of
+    /// wastage events of both players if wastage counting is enabled.
+    fn single_rollout_counting_wastage<U: DiceGen>(
+        &self,
+        from: &Position,
+        dice_gen: &mut U,
+    ) -> (Probabilities, u32) {
         let mut player_on_turn = true;
         let mut pos = *from;
-        loop {
-            let dice = dice_gen.roll();
-            pos = self.evaluator.best_position_by_equity(&pos, &dice);
-            if let GameOver(result) = pos.game_state() {
+        let mut buffer = Vec::new();
+        let mut wastage_events = 0;
+        let mut dice = dice_gen.roll();
+        for half_moves in 1..=MAX_HALF_MOVES {
+            let before = pos;
+            pos = self
+                .evaluator
+                .best_position_by_equity_reusing(&pos, &dice, &mut buffer);
+            if self.count_wastage && is_wastage(&before, &pos, &dice) {
+                wastage_events += 1;
+            }
+            if let Some(probabilities) = self.stop_or_truncate_at(&pos, half_moves) {
                 return if player_on_turn {
-                    result.reverse()
+                    (probabilities.switch_sides(), wastage_events)
                 } else {
-                    result
+                    (probabilities, wastage_events)
                 };
             }
             player_on_turn = !player_on_turn;
+            dice = dice_gen.roll();
         }
+        panic!(
+            "Rollout didn't end after {MAX_HALF_MOVES} half moves, position {} with dice {:?}",
+            pos.position_id(),
+            dice
+        );
+    }
+
+    /// Plays the same game as [RolloutEvaluator::single_rollout_with_dice] followed by
+    /// [RolloutEvaluator::single_rollout_with_generator], but records each half move.
+    fn trace_with_generator<U: DiceGen>(
+        &self,
+        from: &Position,
+        first_dice: &[Dice],
+        dice_gen: &mut U,
+    ) -> GameTrace {
+        let mut trace = Vec::new();
+        let mut player_on_turn = true;
+        let mut pos = *from;
+        let mut buffer = Vec::new();
+        let enumerated = first_dice.len();
+        let mut first_dice = first_dice.iter();
+        for half_moves in 1..=MAX_HALF_MOVES {
+            // Like in a rollout, the random dice are only rolled after the enumerated dice.
+            let dice = first_dice
+                .next()
+                .copied()
+                .unwrap_or_else(|| dice_gen.roll());
+            pos = self
+                .evaluator
+                .best_position_by_equity_reusing(&pos, &dice, &mut buffer);
+            player_on_turn = !player_on_turn;
+            if player_on_turn {
+                trace.push((dice, pos));
+            } else {
+                trace.push((dice, pos.sides_switched()));
+            }
+            if self
+                .stop_or_truncate_at(&pos, half_moves.saturating_sub(enumerated))
+                .is_some()
+            {
+                return trace;
+            }
+        }
+        panic!(
+            "Rollout didn't end after {MAX_HALF_MOVES} half moves, position {}",
+            pos.position_id()
+        );
+    }
+
+    /// Plays the same game as [RolloutEvaluator::trace_with_generator], but `hook` chooses the moves.
+    fn single_rollout_with_hook<U: DiceGen, H>(
+        &self,
+        from: &Position,
+        first_dice: &[Dice],
+        dice_gen: &mut U,
+        hook: &H,
+    ) -> Probabilities
+    where
+        H: Fn(&Position, &Dice, &[Position]) -> usize,
+    {
+        let mut player_on_turn = true;
+        let mut pos = *from;
+        let enumerated = first_dice.len();
+        let mut first_dice = first_dice.iter();
+        for half_moves in 1..=MAX_HALF_MOVES {
+            let dice = first_dice
+                .next()
+                .copied()
+                .unwrap_or_else(|| dice_gen.roll());
+            let candidates = self.candidates(&pos, &dice);
+            pos = *candidates
+                .get(hook(&pos, &dice, &candidates))
+                .expect("The move hook must return the index of one of the candidates.");
+            if let Some(probabilities) =
+                self.stop_or_truncate_at(&pos, half_moves.saturating_sub(enumerated))
+            {
+                return if player_on_turn {
+                    probabilities.switch_sides()
+                } else {
+                    probabilities
+                };
+            }
+            player_on_turn = !player_on_turn;
+        }
+        panic!(
+            "Rollout didn't end after {MAX_HALF_MOVES} half moves, position {}",
+            pos.position_id()
+        );
+    }
+
+    /// All legal moves with `dice`, sorted so that the first one is the move which
+    /// [Evaluator::best_position_by_equity] chooses.
+    ///
+    /// Moves which end the game come first and aren't evaluated. Moves of equal equity keep the
+    /// order of the move generation, because the evaluator picks the first of them.
+    fn candidates(&self, pos: &Position, dice: &Dice) -> Vec<Position> {
+        let (mut candidates, ongoing): (Vec<Position>, Vec<Position>) = pos
+            .all_positions_after_moving(dice)
+            .into_iter()
+            .partition(|position| position.has_lost());
+        let mut evaluated: Vec<(Position, Probabilities)> = self
+            .evaluator
+            .eval_batch(ongoing)
+            .into_iter()
+            .map(|(position, probabilities)| (position, probabilities.switch_sides()))
+            .collect();
+        // `sort_by` is stable, unlike the `sort_unstable_by` of the evaluator.
+        evaluated.sort_by(|(_, a), (_, b)| b.cmp_by_equity(a));
+        candidates.extend(evaluated.into_iter().map(|(position, _)| position));
+        candidates
+    }
+
+    /// Where a single rollout ends: the result of a finished game, or the evaluation of the partial
+    /// evaluator if it has one. `None` if the game has to go on.
+    fn stop_at(&self, pos: &Position) -> Option<Probabilities> {
+        match pos.game_state() {
+            GameOver(result) => Some(Probabilities::from(result)),
+            Ongoing => self.partial.as_ref()?.try_eval(pos),
+        }
+    }
+
+    /// Like [RolloutEvaluator::stop_at], but also stops truncated rollouts after `half_moves`
+    /// random half moves with the evaluation of `pos`.
+    fn stop_or_truncate_at(&self, pos: &Position, half_moves: usize) -> Option<Probabilities> {
+        self.stop_at(pos).or_else(|| match self.truncation {
+            Some(truncation) if half_moves >= truncation => Some(self.evaluator.eval(pos)),
+            _ => None,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::rollout::RolloutEvaluator;
-    use engine::evaluator::Evaluator;
+    use crate::rollout::{RolloutEvaluator, RolloutMode, RolloutStats, greedy_move};
+    use engine::dice::Dice;
+    use engine::dice_gen::DiceGenMock;
+    use engine::evaluator::{Evaluator, EvaluatorFake, PartialEvaluator};
+    use engine::instrumented::InstrumentedEvaluator;
     use engine::pos;
+    use engine::position::GameResult::WinNormal;
+    use engine::position::GameState::{GameOver, Ongoing};
+    use engine::position::Position;
+    use engine::position::presets::closeout_on_bar;
+    use engine::probabilities::Probabilities;
+    use engine::trivial_endgame::TrivialEndgame;
+    use logic::cube::{CubePosition, CubeState, MatchState};
+    use logic::game_context::GameContext;
+    use logic::match_equity::match_equity_after_win;
 
     #[test]
     fn correct_results_after_first_or_second_half_move() {
@@ -165,6 +1237,651 @@ mod tests {
         assert_eq!(results.lose_normal, 0.1875);
     }
 
+    #[test]
+    fn eval_stream_ends_with_full_eval() {
+        // Given
+        // The `RandomEvaluator` isn't deterministic, so we need an evaluator which always picks the same moves.
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let estimates: Vec<_> = rollout_eval.eval_stream(&pos).collect();
+        // Then
+        let games: Vec<usize> = estimates.iter().map(|(games, _)| *games).collect();
+        assert!(games.windows(2).all(|w| w[0] < w[1]));
+        let (games, probabilities) = estimates.last().unwrap();
+        assert_eq!(*games, 1296);
+        assert_eq!(*probabilities, rollout_eval.eval(&pos));
+    }
+
+    #[test]
+    fn eval_stream_can_stop_early() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let (games, _) = rollout_eval.eval_stream(&pos).next().unwrap();
+        // Then
+        assert!(games < 1296);
+    }
+
+    #[test]
+    fn rollout_moves_are_sorted_with_stats() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let moves = rollout_eval.rollout_moves(&pos, &Dice::new(4, 1), 300);
+        // Then
+        assert_eq!(
+            moves.len(),
+            pos.all_positions_after_moving(&Dice::new(4, 1)).len()
+        );
+        assert!(moves.windows(2).all(|w| w[0].2.equity >= w[1].2.equity));
+        for (_, probabilities, stats) in moves {
+            assert_eq!(stats.games, 300);
+            assert_eq!(stats.equity, probabilities.equity());
+            assert!(stats.std_error > 0.0);
+        }
+    }
+
+    #[test]
+    fn rollout_moves_near_identical_moves_overlap() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        // With 2-1 both 12/10 11/10 and 12/9 leave a race with the same pip count.
+        let pos = pos!(x 12:1, 11:1, 6:3; o 19:3, 18:2);
+        // When
+        let moves = rollout_eval.rollout_moves(&pos, &Dice::new(2, 1), 300);
+        // Then
+        let stats_for = |expected: Position| {
+            moves
+                .iter()
+                .find(|(position, _, _)| *position == expected)
+                .unwrap()
+                .2
+        };
+        let point_made = stats_for(pos!(x 10:2, 6:3; o 19:3, 18:2));
+        let run = stats_for(pos!(x 11:1, 9:1, 6:3; o 19:3, 18:2));
+        assert!(point_made.overlaps(&run));
+    }
+
+    #[test]
+    fn rollout_moves_count_wastage_in_gappy_bearoff() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator).with_wastage_counting(true);
+        // Gaps on the 5, 4, 3 and 2 point: bigger numbers are wasted on the ace point soon.
+        let pos = pos!(x 6:4, 1:4; o 24:4, 19:4);
+        // When
+        let moves = rollout_eval.rollout_moves(&pos, &Dice::new(6, 5), 50);
+        // Then
+        assert!(moves.iter().all(|(_, _, stats)| stats.wastage_events > 0));
+    }
+
+    #[test]
+    fn rollout_moves_without_wastage_counting() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:4, 1:4; o 24:4, 19:4);
+        // When
+        let moves = rollout_eval.rollout_moves(&pos, &Dice::new(6, 5), 50);
+        // Then
+        assert!(moves.iter().all(|(_, _, stats)| stats.wastage_events == 0));
+    }
+
+    #[test]
+    fn eval_match_values_gammon_at_gammon_go_score() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        // `x` bears off with any roll, `o` hasn't borne off a checker yet: a certain gammon.
+        let pos = pos!(x 1:1; o 13:15);
+        // 2-away 1-away in the Crawford game: a gammon wins the match, a single win doesn't.
+        let gammon_go = GameContext::new(
+            CubeState::default(),
+            MatchState::from_away(2, 1, true).unwrap(),
+        );
+        // When
+        let money = rollout_eval.eval_match(&pos, &GameContext::default());
+        let mwc = rollout_eval.eval_match(&pos, &gammon_go);
+        // Then
+        assert_eq!(money, 2.0);
+        assert_eq!(mwc, 1.0);
+        assert!(mwc > match_equity_after_win(2, 1, 1));
+    }
+
+    #[test]
+    fn eval_match_cashes_when_opponent_should_pass() {
+        // Given
+        // `x` is such a big favorite that `o` should pass a double at any score.
+        let evaluator = EvaluatorFake::with_default([0.95, 0.0, 0.0, 0.05, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        let pos = pos!(x 1:1; o 13:15);
+        let ctx = GameContext::new(
+            CubeState::with_value(CubePosition::Owned, 2).unwrap(),
+            MatchState::from_away(5, 5, false).unwrap(),
+        );
+        // When
+        let mwc = rollout_eval.eval_match(&pos, &ctx);
+        // Then
+        let cashed = match_equity_after_win(5, 5, 2);
+        assert!((mwc - cashed).abs() < 1e-6, "{mwc} should be {cashed}");
+    }
+
+    #[test]
+    fn eval_match_plays_for_the_doubled_cube_after_a_take() {
+        // Given
+        // With 70% wins `x` should double and `o` should take in a money game.
+        let evaluator = EvaluatorFake::with_default([0.7, 0.0, 0.0, 0.3, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        // `x` bears off with any roll, `o` hasn't borne off a checker yet: a certain gammon.
+        let pos = pos!(x 1:1; o 13:15);
+        // When
+        let equity = rollout_eval.eval_match(&pos, &GameContext::default());
+        // Then
+        assert_eq!(equity, 4.0);
+    }
+
+    #[test]
+    fn rollout_cube_with_beaver_after_wrong_double() {
+        // Given
+        // With 45% wins `x` shouldn't double in a money game, `o` would beaver.
+        let evaluator = EvaluatorFake::with_default([0.45, 0.0, 0.0, 0.55, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        // But `x` bears off with any roll, `o` hasn't borne off a checker yet: a certain gammon.
+        let pos = pos!(x 1:1; o 13:15);
+        // When
+        let result = rollout_eval
+            .rollout_cube(&pos, &GameContext::default())
+            .unwrap();
+        // Then
+        assert_eq!(result.equity_no_double, 2.0);
+        // The beavered cube is at 4.
+        assert_eq!(result.equity_double_take, 8.0);
+        assert_eq!(result.equity_double_pass, 1.0);
+    }
+
+    #[test]
+    fn rollout_cube_without_beaver_after_correct_double() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.7, 0.0, 0.0, 0.3, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        let pos = pos!(x 1:1; o 13:15);
+        let owned = GameContext::new(
+            CubeState::with_value(CubePosition::Owned, 2).unwrap(),
+            MatchState::Money,
+        );
+        // When
+        let result = rollout_eval.rollout_cube(&pos, &owned).unwrap();
+        // Then
+        assert_eq!(result.equity_no_double, 4.0);
+        assert_eq!(result.equity_double_take, 8.0);
+        assert_eq!(result.equity_double_pass, 2.0);
+    }
+
+    #[test]
+    fn rollout_cube_fails_when_opponent_owns_the_cube() {
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let opponent_owned = GameContext::new(
+            CubeState::with_value(CubePosition::OpponentOwned, 2).unwrap(),
+            MatchState::Money,
+        );
+        let result = rollout_eval.rollout_cube(&pos!(x 1:1; o 13:15), &opponent_owned);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn best_position_and_probabilities_never_chooses_nan() {
+        // Given
+        let pos = pos!(x 13:1, 8:1; o 20:2);
+        let dice = Dice::new(4, 2);
+        let moves = pos.all_positions_after_moving(&dice);
+        let mut evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        evaluator.insert(moves[0], [f32::NAN, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        evaluator.insert(moves[1], [-f32::NAN, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        // When
+        let (best, _) = rollout_eval.best_position_and_probabilities(&pos, &dice, |p| p.equity());
+        // Then
+        assert!(moves.len() > 2);
+        assert_eq!(best, moves[2]);
+    }
+
+    #[test]
+    fn eval_match_without_doubles_is_cubeless_eval() {
+        // Given
+        // All positions are even, so nobody ever doubles.
+        let evaluator = EvaluatorFake::with_default([0.4, 0.1, 0.0, 0.4, 0.1, 0.0].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::QuasiRandom { games_per_roll: 4 })
+            .with_antithetic(true);
+        let pos = pos!(x 6:3, 5:3, 4:3; o 19:3, 20:3, 21:3);
+        // When
+        let cubeful = rollout_eval.eval_match(&pos, &GameContext::default());
+        // Then
+        let cubeless = rollout_eval.eval(&pos).equity();
+        assert!(
+            (cubeful - cubeless).abs() < 1e-6,
+            "{cubeful} should be {cubeless}"
+        );
+    }
+
+    #[test]
+    fn eval_match_evaluates_only_for_possible_doubles() {
+        // Given
+        let evaluator = InstrumentedEvaluator::new(EvaluatorFake::with_default(
+            [0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into(),
+        ));
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator)
+            .with_mode(RolloutMode::Enumerated { plies: 1 });
+        // `x` bears off with any roll, so all moves end the game and aren't evaluated.
+        let pos = pos!(x 1:1; o 13:15);
+        let opponent_owned = GameContext::new(
+            CubeState::with_value(CubePosition::OpponentOwned, 2).unwrap(),
+            MatchState::Money,
+        );
+        // When
+        rollout_eval.eval_match(&pos, &opponent_owned);
+        let without_cube_decision = rollout_eval.evaluator.eval_count();
+        rollout_eval.eval_match(&pos, &GameContext::default());
+        // Then
+        assert_eq!(without_cube_decision, 0);
+        // The root is evaluated once for the cube decision of all games.
+        assert_eq!(rollout_eval.evaluator.eval_count(), 1);
+    }
+
+    #[test]
+    fn eval_with_greedy_move_hook_is_eval() {
+        // Given
+        let mut evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        evaluator.insert(
+            pos!(x 5:1, 4:1, 2:1; o 19:1, 20:2).sides_switched(),
+            [0.1, 0.0, 0.0, 0.9, 0.0, 0.0].into(),
+        );
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let hooked = rollout_eval.eval_with_move_hook(&pos, greedy_move);
+        // Then
+        assert_eq!(hooked, rollout_eval.eval(&pos));
+    }
+
+    #[test]
+    fn eval_with_move_hook_plays_chosen_moves() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let worst_move = |_: &Position, _: &Dice, candidates: &[Position]| candidates.len() - 1;
+        let hooked = rollout_eval.eval_with_move_hook(&pos, worst_move);
+        // Then
+        assert_ne!(hooked, rollout_eval.eval(&pos));
+    }
+
+    /// Winning chances of the Kleinman count, good enough to compare rollouts of races.
+    struct RaceEstimate;
+
+    impl Evaluator for RaceEstimate {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            let win = pos.race_estimate().unwrap_or(0.5);
+            [win, 0.0, 0.0, 1.0 - win, 0.0, 0.0].into()
+        }
+    }
+
+    #[test]
+    fn quick_rollout_is_cheaper_with_similar_equity() {
+        // Given
+        let pos = pos!(x 6:5, 5:5, 4:5; o 19:5, 20:5, 21:5);
+        let full = RolloutEvaluator::with_evaluator(InstrumentedEvaluator::new(RaceEstimate));
+        let quick = RolloutEvaluator::quick(InstrumentedEvaluator::new(RaceEstimate));
+        // When
+        let full_equity = full.eval(&pos).equity();
+        let quick_equity = quick.eval(&pos).equity();
+        // Then
+        assert_eq!(full.mode.games(), 1296);
+        assert_eq!(quick.mode.games(), 576);
+        let full_count = full.evaluator.eval_count();
+        let quick_count = quick.evaluator.eval_count();
+        assert!(
+            quick_count * 2 < full_count,
+            "{quick_count} evaluations should be much less than {full_count}"
+        );
+        assert!(
+            (quick_equity - full_equity).abs() < 0.05,
+            "{quick_equity} should be close to {full_equity}"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Rollouts need at least one random half move.")]
+    fn truncation_needs_at_least_one_half_move() {
+        RolloutEvaluator::with_random_evaluator().with_truncation(0);
+    }
+
+    #[test]
+    fn rollout_stats_of_certain_result() {
+        let stats = RolloutStats::new(&[0.0, 1.0, 0.0, 0.0, 0.0, 0.0].into(), 100);
+        assert_eq!(stats.equity, 2.0);
+        assert_eq!(stats.std_error, 0.0);
+        assert_eq!(stats.confidence_interval(), (2.0, 2.0));
+    }
+
+    #[test]
+    fn rollout_stats_confidence_interval() {
+        // Half the games are won, half are lost, so the variance is 1.
+        let stats = RolloutStats::new(&[0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into(), 101);
+        assert_eq!(stats.equity, 0.0);
+        assert!((stats.std_error - 0.1).abs() < 0.001);
+        let other = RolloutStats {
+            games: 101,
+            equity: 0.5,
+            std_error: 0.1,
+            wastage_events: 0,
+        };
+        assert!(!stats.overlaps(&other));
+        assert!(stats.overlaps(&RolloutStats {
+            equity: 0.3,
+            ..other
+        }));
+    }
+
+    #[test]
+    fn difference_significant_with_far_apart_equities() {
+        let stats = RolloutStats {
+            games: 1296,
+            equity: 0.4,
+            std_error: 0.02,
+            wastage_events: 0,
+        };
+        let other = RolloutStats {
+            equity: 0.2,
+            ..stats
+        };
+        assert!(stats.difference_significant(&other));
+        assert!(other.difference_significant(&stats));
+    }
+
+    #[test]
+    fn difference_not_significant_with_large_errors() {
+        let stats = RolloutStats {
+            games: 36,
+            equity: 0.4,
+            std_error: 0.15,
+            wastage_events: 0,
+        };
+        let other = RolloutStats {
+            equity: 0.35,
+            ..stats
+        };
+        assert!(!stats.difference_significant(&other));
+        assert!(!stats.difference_significant(&stats));
+    }
+
+    #[test]
+    fn quasi_random_mode_plays_36_games_per_roll() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let mode = RolloutMode::QuasiRandom { games_per_roll: 5 };
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator).with_mode(mode);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let (games, probabilities) = rollout_eval.eval_stream(&pos).last().unwrap();
+        // Then
+        assert_eq!(games, 36 * 5);
+        assert_eq!(mode.games(), 36 * 5);
+        assert_eq!(probabilities, rollout_eval.eval(&pos));
+    }
+
+    #[test]
+    fn quasi_random_mode_is_close_to_full_rollout() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let full = RolloutEvaluator::with_evaluator(evaluator);
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let mode = RolloutMode::QuasiRandom { games_per_roll: 36 };
+        let quasi_random = RolloutEvaluator::with_evaluator(evaluator).with_mode(mode);
+        let pos = pos!(x 8:2, 6:3, 4:2; o 17:2, 19:3, 21:2);
+        // When
+        let full_probabilities = full.eval(&pos);
+        let quasi_random_probabilities = quasi_random.eval(&pos);
+        // Then
+        let full_stats = RolloutStats::new(&full_probabilities, 1296);
+        let quasi_random_stats = RolloutStats::new(&quasi_random_probabilities, 1296);
+        assert!(full_stats.overlaps(&quasi_random_stats));
+        assert!((full_probabilities.win() - quasi_random_probabilities.win()).abs() < 0.05);
+    }
+
+    #[test]
+    fn one_enumerated_ply_plays_36_games_and_is_close_to_full_rollout() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let full = RolloutEvaluator::with_evaluator(evaluator);
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let one_ply = RolloutEvaluator::with_evaluator(evaluator).with_enumerated_plies(1);
+        let pos = pos!(x 8:2, 6:3, 4:2; o 17:2, 19:3, 21:2);
+        // When
+        let (games, one_ply_probabilities) = one_ply.eval_stream(&pos).last().unwrap();
+        let full_probabilities = full.eval(&pos);
+        // Then
+        assert_eq!(games, 36);
+        assert_eq!(one_ply_probabilities, one_ply.eval(&pos));
+        let full_stats = RolloutStats::new(&full_probabilities, 1296);
+        let one_ply_stats = RolloutStats::new(&one_ply_probabilities, 36);
+        assert!(full_stats.overlaps(&one_ply_stats));
+    }
+
+    #[test]
+    fn two_enumerated_plies_are_the_full_rollout() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let full = RolloutEvaluator::with_evaluator(evaluator);
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let two_plies = RolloutEvaluator::with_evaluator(evaluator).with_enumerated_plies(2);
+        let pos = pos!(x 6:2, 5:1; o 19:1, 20:2);
+        // When
+        let two_plies_probabilities = two_plies.eval(&pos);
+        // Then
+        assert_eq!(two_plies_probabilities, full.eval(&pos));
+        assert_eq!(RolloutMode::Enumerated { plies: 3 }.games(), 46656);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only 1, 2 or 3 plies can be enumerated.")]
+    fn four_enumerated_plies_are_not_supported() {
+        let _ = RolloutEvaluator::with_random_evaluator().with_enumerated_plies(4);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only 1, 2 or 3 plies can be enumerated.")]
+    fn zero_enumerated_plies_are_not_a_mode() {
+        let _ = RolloutEvaluator::with_random_evaluator()
+            .with_mode(RolloutMode::Enumerated { plies: 0 });
+    }
+
+    #[test]
+    #[should_panic(expected = "Quasi random rollouts need at least one game per roll.")]
+    fn quasi_random_mode_needs_games() {
+        let _ = RolloutEvaluator::with_random_evaluator()
+            .with_mode(RolloutMode::QuasiRandom { games_per_roll: 0 });
+    }
+
+    #[test]
+    fn eval_with_scripted_dice() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator().with_enumerated_plies(1);
+        let pos = pos!(x 6:1; o 19:1);
+        // `x` doesn't finish with 9 of the 36 first rolls: 21, 12, 31, 13, 51, 15, 32, 23 and 11.
+        // In all those 9 games `o` finishes with the scripted 66.
+        let mut dice_gen = DiceGenMock::new(&[Dice::new(6, 6); 9]);
+        // When
+        let results = rollout_eval.eval_with_dice(&pos, &mut dice_gen);
+        // Then
+        dice_gen.assert_all_dice_were_used();
+        assert_eq!(results.win_normal, 0.75);
+        assert_eq!(results.lose_normal, 0.25);
+    }
+
+    #[test]
+    fn partial_evaluator_stops_rollouts_without_random_dice() {
+        // Given
+        let pos = pos!(x 10:1; o 15:1);
+        let rollout_eval = RolloutEvaluator::new_partial(
+            TrivialEndgame::new(),
+            EvaluatorFake::with_default(Probabilities::default()),
+        )
+        .with_enumerated_plies(1);
+        // After the first roll, `TrivialEndgame` can evaluate each game, so no random dice are needed.
+        let mut dice_gen = DiceGenMock::new(&[]);
+        // When
+        let results = rollout_eval.eval_with_dice(&pos, &mut dice_gen);
+        // Then
+        // `TrivialEndgame` is exact, so averaging it over all first rolls gives its value before the roll.
+        let exact = TrivialEndgame::new().try_eval(&pos).unwrap();
+        assert!((results.win_normal - exact.win_normal).abs() < 0.0001);
+        assert!((results.lose_normal - exact.lose_normal).abs() < 0.0001);
+        assert_eq!(results.win_gammon, 0.0);
+    }
+
+    #[test]
+    fn eval_with_samples_returns_finished_games() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator).with_enumerated_plies(1);
+        let pos = pos!(x 8:2, 6:3, 4:2; o 17:2, 19:3, 21:2);
+        // When
+        let (probabilities, traces) = rollout_eval.eval_with_samples(&pos, 10);
+        // Then
+        assert_eq!(probabilities, rollout_eval.eval(&pos));
+        assert_eq!(traces.len(), 10);
+        for trace in &traces {
+            let (_, last) = trace.last().unwrap();
+            assert_ne!(last.game_state(), Ongoing);
+            let (_, before_last) = trace.iter().rev().nth(1).unwrap();
+            assert_eq!(before_last.game_state(), Ongoing);
+        }
+        let (_, more_than_all) = rollout_eval.eval_with_samples(&pos, 100);
+        assert_eq!(more_than_all.len(), 36);
+    }
+
+    #[test]
+    fn antithetic_rollouts_have_lower_standard_error() {
+        // Given
+        // A race, where the result mostly depends on how big the rolls are.
+        let pos = pos!(x 6:3, 5:3, 4:3; o 19:3, 20:3, 21:3);
+        let mode = RolloutMode::QuasiRandom { games_per_roll: 2 };
+        let equities = |antithetic: bool| -> Vec<f32> {
+            (0..30)
+                .map(|seed| {
+                    let evaluator =
+                        EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+                    RolloutEvaluator::with_evaluator_and_seed(evaluator, seed)
+                        .with_mode(mode)
+                        .with_antithetic(antithetic)
+                        .eval(&pos)
+                        .equity()
+                })
+                .collect()
+        };
+        let standard_error = |equities: Vec<f32>| {
+            let n = equities.len() as f32;
+            let mean = equities.iter().sum::<f32>() / n;
+            let variance = equities.iter().map(|e| (e - mean).powi(2)).sum::<f32>() / (n - 1.0);
+            variance.sqrt()
+        };
+        // When
+        let plain = standard_error(equities(false));
+        let antithetic = standard_error(equities(true));
+        // Then
+        assert!(
+            antithetic < plain,
+            "{antithetic} should be less than {plain}"
+        );
+    }
+
+    #[test]
+    fn eval_after_move_with_best_and_worse_root() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        let pos = pos!(x 6:1, 4:1; o 19:1, 20:1);
+        let dice = Dice::new(4, 2);
+        // One checker on the 4 point bears off with almost every roll, two checkers on the 2 point don't.
+        let best = pos!(x 4:1; o 19:1, 20:1).sides_switched();
+        let worse = pos!(x 2:2; o 19:1, 20:1).sides_switched();
+        // When
+        let best_probabilities = rollout_eval.eval_after_move(&pos, &dice, &best).unwrap();
+        let worse_probabilities = rollout_eval.eval_after_move(&pos, &dice, &worse).unwrap();
+        // Then
+        assert!(best_probabilities.equity() > worse_probabilities.equity());
+        let illegal = pos!(x 1:1; o 19:1, 20:1).sides_switched();
+        assert!(rollout_eval.eval_after_move(&pos, &dice, &illegal).is_err());
+    }
+
+    #[test]
+    fn eval_after_move_with_best_root_is_eval() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let rollout_eval = RolloutEvaluator::with_evaluator(evaluator);
+        // `x` is on the bar against a closed board, so the best move with any roll is not moving.
+        let pos = closeout_on_bar();
+        let best = pos.sides_switched();
+        // When
+        let after_move = rollout_eval
+            .eval_after_move(&pos, &Dice::new(6, 5), &best)
+            .unwrap();
+        // Then
+        assert_eq!(after_move, rollout_eval.eval(&pos));
+    }
+
+    #[test]
+    fn trace_single_until_game_over() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 6:1; o 19:1);
+        // When
+        let trace = rollout_eval
+            .trace_single(&pos, &[(2, 1), (1, 2), (6, 6), (3, 3)])
+            .unwrap();
+        // Then
+        let expected = vec![
+            pos,
+            pos!(x 3:1; o 19:1),
+            pos!(x 3:1; o 22:1),
+            pos!(x 3:1; o).sides_switched(),
+        ];
+        assert_eq!(trace, expected);
+        assert_eq!(trace.last().unwrap().game_state(), GameOver(WinNormal));
+    }
+
+    #[test]
+    fn trace_single_fails_when_dice_run_out() {
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        let pos = pos!(x 6:1; o 19:1);
+        let trace = rollout_eval.trace_single(&pos, &[(2, 1), (1, 2)]);
+        assert!(trace.is_err());
+    }
+
+    #[test]
+    fn info_is_rollout_with_strength_of_evaluator() {
+        let evaluator = EvaluatorFake::with_no_default();
+        let info = RolloutEvaluator::with_evaluator(evaluator).info();
+        assert_eq!(info.name, "Rollout");
+        assert_eq!(info.approx_ply, 0);
+        assert!(info.is_rollout);
+    }
+
     #[test]
     fn rollout_always_lose_gammon() {
         let rollout_eval = RolloutEvaluator::with_random_evaluator();
@@ -185,9 +1902,9 @@ mod tests {
 
 #[cfg(test)]
 mod private_tests {
-    use crate::rollout::RolloutEvaluator;
+    use crate::rollout::{GameResults, RolloutEvaluator, RolloutStats, combine_in_order};
     use engine::dice::Dice;
-    use engine::dice_gen::DiceGenMock;
+    use engine::dice_gen::{DiceGenMock, FastrandDice};
     use engine::pos;
     use engine::position::GameResult::{
         LoseBg, LoseGammon, LoseNormal, WinBg, WinGammon, WinNormal,
@@ -203,7 +1920,7 @@ mod private_tests {
         let result = rollout_eval.single_rollout_with_generator(&pos, &mut dice_gen);
         //Then
         dice_gen.assert_all_dice_were_used();
-        assert_eq!(result, WinNormal);
+        assert_eq!(result, WinNormal.into());
     }
 
     #[test]
@@ -221,7 +1938,20 @@ mod private_tests {
         let result = rollout_eval.single_rollout_with_generator(&pos, &mut dice_gen);
         // Then
         dice_gen.assert_all_dice_were_used();
-        assert_eq!(result, LoseNormal);
+        assert_eq!(result, LoseNormal.into());
+    }
+
+    #[test]
+    #[should_panic(expected = "Rollout didn't end after 10000 half moves")]
+    fn single_rollout_with_generator_stops_when_nobody_can_move() {
+        // Given
+        let rollout_eval = RolloutEvaluator::with_random_evaluator();
+        // Both players are on the bar and closed out, so the game would go on forever.
+        let pos = pos!(x 25:1, 7:2, 6:2, 5:2, 4:2, 3:2, 2:2, 1:2; o 0:1, 18:2, 19:2, 20:2, 21:2, 22:2, 23:2, 24:2);
+        assert_eq!(pos.validate(), Ok(()));
+        // When
+        let mut dice_gen = FastrandDice::with_seed(0);
+        rollout_eval.single_rollout_with_generator(&pos, &mut dice_gen);
     }
 
     #[test]
@@ -233,7 +1963,7 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(2, 2), Dice::new(6, 6)]);
         //Then
-        assert_eq!(result, Ok(WinGammon));
+        assert_eq!(result, Ok(WinGammon.into()));
     }
 
     #[test]
@@ -245,7 +1975,7 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(2, 1), Dice::new(3, 3)]);
         //Then
-        assert_eq!(result, Ok(LoseGammon));
+        assert_eq!(result, Ok(LoseGammon.into()));
     }
 
     #[test]
@@ -257,7 +1987,7 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(6, 6), Dice::new(1, 2)]);
         //Then
-        assert_eq!(result, Ok(WinBg));
+        assert_eq!(result, Ok(WinBg.into()));
     }
 
     #[test]
@@ -269,7 +1999,7 @@ mod private_tests {
         let result =
             rollout_eval.single_rollout_with_dice(&pos, &[Dice::new(1, 2), Dice::new(6, 6)]);
         //Then
-        assert_eq!(result, Ok(LoseBg));
+        assert_eq!(result, Ok(LoseBg.into()));
     }
 
     #[test]
@@ -284,4 +2014,54 @@ mod private_tests {
         let expected_position = pos!(x 1:13; o 24:3);
         assert_eq!(result, Err(expected_position));
     }
+
+    #[test]
+    fn game_results_count_finished_games_exactly() {
+        // Given
+        let mut finished = GameResults::default();
+        finished.add_results(WinNormal.into(), 2);
+        finished.add(LoseGammon.into());
+        let mut fractional = GameResults::default();
+        fractional.add([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        // When
+        let combined = combine_in_order(vec![finished, fractional]);
+        // Then
+        assert_eq!(combined.counter.sum(), 3);
+        assert_eq!(combined.fractional_games, 1);
+        assert_eq!(
+            combined.average(),
+            [0.625, 0.0, 0.0, 0.125, 0.25, 0.0].into()
+        );
+    }
+
+    #[test]
+    fn rollout_stats_of_fractional_results() {
+        // Given
+        let mut game_results = GameResults::default();
+        // Every game ends with an equity of 0, so there is no variance.
+        game_results.add_results([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into(), 100);
+        // When
+        let stats = RolloutStats::from_results(&game_results.average(), &game_results);
+        // Then
+        assert_eq!(stats.games, 100);
+        assert_eq!(stats.equity, 0.0);
+        assert_eq!(stats.std_error, 0.0);
+        // Counted as finished games, half of them won and half lost, the variance would be 1.
+        assert!(RolloutStats::new(&game_results.average(), 100).std_error > 0.09);
+    }
+
+    #[test]
+    fn rollout_stats_of_finished_results_like_new() {
+        // Given
+        let mut game_results = GameResults::default();
+        game_results.add_results(WinGammon.into(), 30);
+        game_results.add_results(LoseNormal.into(), 70);
+        let probabilities = game_results.average();
+        // When
+        let stats = RolloutStats::from_results(&probabilities, &game_results);
+        // Then
+        let expected = RolloutStats::new(&probabilities, 100);
+        assert_eq!(stats.equity, expected.equity);
+        assert!((stats.std_error - expected.std_error).abs() < 0.00001);
+    }
 }
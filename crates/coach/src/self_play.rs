@@ -0,0 +1,226 @@
+use engine::dice_gen::{DiceGen, FastrandDice};
+use engine::evaluator::Evaluator;
+use engine::position::GameState::{GameOver, Ongoing};
+use engine::position::{GameResult, Position, STARTING};
+use engine::probabilities::Probabilities;
+use rayon::prelude::*;
+
+/// A position of a self-play game together with the result of that game.
+///
+/// Both are from the perspective of player `x`, who is on roll in `position`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    pub position: Position,
+    pub result: GameResult,
+}
+
+/// Plays games of an evaluator against itself and returns the positions with the final results,
+/// for example as training data.
+///
+/// By default each move is the best move by equity. Greedy self-play leads to very similar games,
+/// so [SelfPlayGenerator::with_temperature] adds noise to the moves early in the game.
+/// Game number `i` is always played with the same dice and noise, derived from `seed` and `i`.
+pub struct SelfPlayGenerator<T: Evaluator> {
+    evaluator: T,
+    seed: u64,
+    temperature: f32,
+    exploration_half_moves: usize,
+}
+
+impl<T: Evaluator> SelfPlayGenerator<T> {
+    /// Greedy self-play without any noise.
+    pub fn new(evaluator: T, seed: u64) -> Self {
+        Self {
+            evaluator,
+            seed,
+            temperature: 0.0,
+            exploration_half_moves: 0,
+        }
+    }
+
+    /// Explores different moves during the first `exploration_half_moves` half moves of each game,
+    /// all later moves are greedy again.
+    ///
+    /// Before picking the best move, Gumbel noise scaled with `temperature` is added to the equity
+    /// of each legal move. This is the same as choosing a move with the probability proportional to
+    /// `exp(equity / temperature)`: with a temperature of `0` the best move is always played,
+    /// higher temperatures choose worse moves more often.
+    pub fn with_temperature(self, temperature: f32, exploration_half_moves: usize) -> Self {
+        Self {
+            temperature,
+            exploration_half_moves,
+            ..self
+        }
+    }
+
+    /// Plays `num_games` games one after another and returns the samples of all games in order.
+    pub fn generate(&self, num_games: usize) -> Vec<Sample> {
+        (0..num_games)
+            .flat_map(|game| self.play_game(game))
+            .collect()
+    }
+
+    /// Like [SelfPlayGenerator::generate], but the games are played in parallel on `threads` threads.
+    ///
+    /// Each game is seeded from `seed` and its number, so the samples and their order are the same
+    /// as with `generate`, regardless of the number of threads. Panics if the thread pool can't be built.
+    pub fn generate_parallel(&self, num_games: usize, threads: usize) -> Vec<Sample>
+    where
+        T: Sync,
+    {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .expect("Thread pool for self-play should be built");
+        pool.install(|| {
+            (0..num_games)
+                .into_par_iter()
+                .flat_map_iter(|game| self.play_game(game))
+                .collect()
+        })
+    }
+
+    /// All positions of game number `game` before each move, each with the result of the game.
+    fn play_game(&self, game: usize) -> Vec<Sample> {
+        let game_seed = self.seed.wrapping_add(game as u64);
+        let mut dice_gen = FastrandDice::with_seed(game_seed);
+        let mut noise = fastrand::Rng::with_seed(game_seed);
+        let mut positions = Vec::new();
+        let mut pos = STARTING;
+        let mut dice = dice_gen.roll_mixed();
+        loop {
+            positions.push(pos);
+            let candidates = self
+                .evaluator
+                .positions_and_probabilities_by_equity(&pos, &dice);
+            let chosen = if positions.len() <= self.exploration_half_moves {
+                self.explore(&candidates, &mut noise)
+            } else {
+                candidates[0].0
+            };
+            pos = chosen.sides_switched();
+            match pos.game_state() {
+                Ongoing => dice = dice_gen.roll(),
+                GameOver(result) => return samples(positions, result),
+            }
+        }
+    }
+
+    /// Picks the move with the highest equity after adding noise, see [SelfPlayGenerator::with_temperature].
+    fn explore(
+        &self,
+        candidates: &[(Position, Probabilities)],
+        noise: &mut fastrand::Rng,
+    ) -> Position {
+        if self.temperature <= 0.0 {
+            return candidates[0].0;
+        }
+        let gumbel = |noise: &mut fastrand::Rng| -(-noise.f32().max(f32::MIN_POSITIVE).ln()).ln();
+        candidates
+            .iter()
+            .map(|(position, probabilities)| {
+                let value = probabilities.equity() + self.temperature * gumbel(noise);
+                (position, value)
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(position, _)| *position)
+            .expect("move generator must always return a move")
+    }
+}
+
+/// The positions of a game alternate between both players. `result` is from the perspective
+/// of the loser, who would be on roll after the last position.
+fn samples(positions: Vec<Position>, result: GameResult) -> Vec<Sample> {
+    let winner_result = result.reverse();
+    let last = positions.len() - 1;
+    positions
+        .into_iter()
+        .enumerate()
+        .map(|(index, position)| {
+            let result = if (last - index).is_multiple_of(2) {
+                winner_result.clone()
+            } else {
+                result.clone()
+            };
+            Sample { position, result }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::self_play::SelfPlayGenerator;
+    use engine::evaluator::EvaluatorFake;
+    use engine::position::GameResult::{WinBg, WinGammon, WinNormal};
+    use engine::position::GameState::Ongoing;
+    use engine::position::{Position, STARTING};
+    use std::collections::HashSet;
+
+    fn evaluator_fake() -> EvaluatorFake {
+        EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into())
+    }
+
+    fn distinct_opening_moves(generator: &SelfPlayGenerator<EvaluatorFake>, games: usize) -> usize {
+        let samples = generator.generate(games);
+        // The second position of each game is the position after the opening move.
+        let openings: HashSet<Position> = samples
+            .windows(2)
+            .filter(|pair| pair[0].position == STARTING)
+            .map(|pair| pair[1].position)
+            .collect();
+        openings.len()
+    }
+
+    #[test]
+    fn samples_alternate_with_result_of_game() {
+        // Given
+        let generator = SelfPlayGenerator::new(evaluator_fake(), 0);
+        // When
+        let samples = generator.generate(1);
+        // Then
+        assert_eq!(samples[0].position, STARTING);
+        for sample in &samples {
+            assert_eq!(sample.position.game_state(), Ongoing);
+        }
+        for pair in samples.windows(2) {
+            assert_eq!(pair[1].result, pair[0].result.reverse());
+        }
+        // The player who made the last move has won.
+        assert!(matches!(
+            samples.last().unwrap().result,
+            WinNormal | WinGammon | WinBg
+        ));
+    }
+
+    #[test]
+    fn same_seed_plays_same_games() {
+        let generator = SelfPlayGenerator::new(evaluator_fake(), 3).with_temperature(1.0, 4);
+        assert_eq!(generator.generate(2), generator.generate(2));
+    }
+
+    #[test]
+    fn parallel_games_are_independent_of_thread_count() {
+        // Given
+        let generator = SelfPlayGenerator::new(evaluator_fake(), 7).with_temperature(1.0, 4);
+        // When
+        let single_thread = generator.generate_parallel(6, 1);
+        let four_threads = generator.generate_parallel(6, 4);
+        // Then
+        assert_eq!(single_thread, four_threads);
+        assert_eq!(single_thread, generator.generate(6));
+    }
+
+    #[test]
+    fn higher_temperature_explores_more_opening_moves() {
+        // Given
+        let greedy = SelfPlayGenerator::new(evaluator_fake(), 0);
+        let exploring = SelfPlayGenerator::new(evaluator_fake(), 0).with_temperature(1.0, 2);
+        // When
+        let greedy_openings = distinct_opening_moves(&greedy, 30);
+        let exploring_openings = distinct_opening_moves(&exploring, 30);
+        // Then
+        // Greedy play has at most one opening move for each of the 15 opening rolls.
+        assert!(greedy_openings <= 15);
+        assert!(exploring_openings > greedy_openings);
+    }
+}
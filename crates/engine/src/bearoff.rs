@@ -0,0 +1,572 @@
+use crate::dice::ALL_21;
+use crate::evaluator::PartialEvaluator;
+use crate::position::{NUM_OF_CHECKERS, O_BAR, Player, Position, X_BAR};
+use crate::probabilities::Probabilities;
+use memmap2::Mmap;
+use std::fs::File;
+use std::path::Path;
+
+/// Number of points in the home board. All checkers of both players have to be there.
+const POINTS: usize = 6;
+
+/// First bytes of a file containing a [BearoffDatabase].
+const MAGIC: &[u8; 8] = b"wildbgbo";
+const VERSION: u8 = 1;
+/// Magic, version, number of checkers, rest is reserved.
+const HEADER_LEN: usize = 16;
+const ENTRY_LEN: usize = size_of::<f32>();
+
+/// Two-sided bearoff database: exact winning probabilities for all positions in which both
+/// players have at most `checkers` checkers left, all of them in their home boards.
+///
+/// As both players have already borne off at least one checker, there are no gammons.
+///
+/// The probabilities are stored as little endian `f32` after a short header. The index of a position
+/// is computed from the checker distributions of both players, so each lookup is O(1).
+/// With [BearoffDatabase::open_mmap] the file is memory-mapped and only paged in when needed.
+pub struct BearoffDatabase {
+    checkers: u8,
+    /// Number of possible checker distributions for a single player.
+    one_sided_count: usize,
+    storage: Storage,
+}
+
+enum Storage {
+    Memory(Vec<u8>),
+    Mapped(Mmap),
+}
+
+impl Storage {
+    #[inline]
+    fn bytes(&self) -> &[u8] {
+        match self {
+            Storage::Memory(bytes) => bytes,
+            Storage::Mapped(mmap) => mmap,
+        }
+    }
+}
+
+impl PartialEvaluator for BearoffDatabase {
+    /// Returns `None` unless both players have at most `checkers` checkers left,
+    /// all of them in their home board.
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+        let index = self.index(pos)?;
+        let win = self.win_probability(index);
+        Some(Probabilities {
+            win_normal: win,
+            lose_normal: 1.0 - win,
+            ..Default::default()
+        })
+    }
+}
+
+impl BearoffDatabase {
+    /// Calculates the database in memory. This may take a while for more than 6 checkers.
+    ///
+    /// `checkers` has to be between 1 and 14. With 15 checkers gammons would be possible.
+    pub fn generate(checkers: u8) -> Self {
+        assert!(
+            (1..NUM_OF_CHECKERS).contains(&checkers),
+            "A bearoff database needs between 1 and 14 checkers."
+        );
+        let one_sided_count = binomial(checkers as usize + POINTS, POINTS);
+        let distributions = all_distributions(checkers);
+
+        // The winning probability of a position only depends on positions with fewer pips in total.
+        // So we group the distributions by pip count and calculate the positions in that order.
+        let max_pips = POINTS * checkers as usize;
+        let mut by_pips: Vec<Vec<[u8; POINTS]>> = vec![Vec::new(); max_pips + 1];
+        for distribution in distributions {
+            by_pips[pip_count(&distribution)].push(distribution);
+        }
+
+        let mut values = vec![0.0_f32; one_sided_count * one_sided_count];
+        for total_pips in 0..=2 * max_pips {
+            for x_pips in total_pips.saturating_sub(max_pips)..=total_pips.min(max_pips) {
+                for x in &by_pips[x_pips] {
+                    for o in &by_pips[total_pips - x_pips] {
+                        let index = one_sided_index(x) * one_sided_count + one_sided_index(o);
+                        values[index] = Self::calculate(x, o, &values, one_sided_count);
+                    }
+                }
+            }
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + values.len() * ENTRY_LEN);
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(checkers);
+        bytes.resize(HEADER_LEN, 0);
+        for value in values {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        Self {
+            checkers,
+            one_sided_count,
+            storage: Storage::Memory(bytes),
+        }
+    }
+
+    /// Reads the whole database from a file into memory.
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+        Self::with_storage(Storage::Memory(bytes))
+    }
+
+    /// Memory-maps the database from a file. Only the parts that are looked up are paged in.
+    pub fn open_mmap(path: &Path) -> Result<Self, String> {
+        let file = File::open(path).map_err(|error| error.to_string())?;
+        // SAFETY: The file is only read. If it's modified by another process while mapped,
+        // lookups may return garbage, but the length of the map doesn't change.
+        let mmap = unsafe { Mmap::map(&file) }.map_err(|error| error.to_string())?;
+        Self::with_storage(Storage::Mapped(mmap))
+    }
+
+    /// Writes the database to a file, which can later be read with `open` or `open_mmap`.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        std::fs::write(path, self.storage.bytes())
+    }
+
+    /// Maximum number of checkers per player in this database.
+    #[inline]
+    pub fn checkers(&self) -> u8 {
+        self.checkers
+    }
+
+    fn with_storage(storage: Storage) -> Result<Self, String> {
+        let bytes = storage.bytes();
+        if bytes.len() < HEADER_LEN || &bytes[..MAGIC.len()] != MAGIC {
+            return Err("Not a bearoff database.".to_string());
+        }
+        if bytes[8] != VERSION {
+            return Err(format!(
+                "Unsupported bearoff database version {}.",
+                bytes[8]
+            ));
+        }
+        let checkers = bytes[9];
+        if !(1..NUM_OF_CHECKERS).contains(&checkers) {
+            return Err(format!("Invalid number of checkers {checkers}."));
+        }
+        let one_sided_count = binomial(checkers as usize + POINTS, POINTS);
+        if bytes.len() != HEADER_LEN + one_sided_count * one_sided_count * ENTRY_LEN {
+            return Err("Bearoff database has the wrong size.".to_string());
+        }
+        Ok(Self {
+            checkers,
+            one_sided_count,
+            storage,
+        })
+    }
+
+    /// Probability that the player on roll wins.
+    #[inline]
+    fn win_probability(&self, index: usize) -> f32 {
+        let start = HEADER_LEN + index * ENTRY_LEN;
+        let bytes = &self.storage.bytes()[start..start + ENTRY_LEN];
+        f32::from_le_bytes(bytes.try_into().unwrap())
+    }
+
+    /// Index of the position in the database, `None` if it's not part of the database.
+    fn index(&self, pos: &Position) -> Option<usize> {
+        let (x, o) = distributions(pos)?;
+        let in_range = |distribution: &[u8; POINTS]| {
+            let checkers: u8 = distribution.iter().sum();
+            checkers > 0 && checkers <= self.checkers
+        };
+        if in_range(&x) && in_range(&o) {
+            Some(one_sided_index(&x) * self.one_sided_count + one_sided_index(&o))
+        } else {
+            None
+        }
+    }
+
+    /// Winning probability for the player on roll with checkers `x`, opponent has checkers `o`.
+    ///
+    /// All positions with fewer pips must already be stored in `values`.
+    fn calculate(
+        x: &[u8; POINTS],
+        o: &[u8; POINTS],
+        values: &[f32],
+        one_sided_count: usize,
+    ) -> f32 {
+        if x.iter().all(|&c| c == 0) {
+            return 1.0;
+        }
+        if o.iter().all(|&c| c == 0) {
+            return 0.0;
+        }
+        let pos = position(x, o);
+        let mut sum = 0.0;
+        for (dice, amount) in ALL_21 {
+            let best = pos
+                .all_positions_after_moving(&dice)
+                .iter()
+                .map(|after| {
+                    if after.has_lost() {
+                        // The player who just moved has borne off all checkers.
+                        1.0
+                    } else {
+                        let (opponent, own) = distributions(after)
+                            .expect("Bearoff positions can only lead to bearoff positions");
+                        let index =
+                            one_sided_index(&opponent) * one_sided_count + one_sided_index(&own);
+                        1.0 - values[index]
+                    }
+                })
+                .fold(0.0_f32, f32::max);
+            sum += best * amount as f32;
+        }
+        sum / 36.0
+    }
+}
+
+/// One-sided bearoff table: for each distribution of up to `checkers` checkers in the home board,
+/// the probabilities to bear off all of them in exactly `k` rolls.
+///
+/// Only the own checkers are taken into account, each roll is played to minimize the expected
+/// number of rolls. Useful for race math and the user interface, not for exact evaluations of
+/// both players like [BearoffDatabase].
+pub struct OneSidedBearoff {
+    checkers: u8,
+    /// Indexed by [one_sided_index], at index `k` the probability of needing exactly `k` rolls.
+    rolls: Vec<Vec<f32>>,
+}
+
+impl OneSidedBearoff {
+    /// Calculates the table in memory, `checkers` has to be between 1 and 15.
+    pub fn generate(checkers: u8) -> Self {
+        assert!(
+            (1..=NUM_OF_CHECKERS).contains(&checkers),
+            "A one-sided bearoff table needs between 1 and 15 checkers."
+        );
+        // Moves only lead to distributions with fewer pips, so they are calculated first.
+        let mut distributions = all_distributions(checkers);
+        distributions.sort_by_key(pip_count);
+        let mut rolls = vec![Vec::new(); distributions.len()];
+        for distribution in distributions {
+            rolls[one_sided_index(&distribution)] = Self::calculate(&distribution, &rolls);
+        }
+        Self { checkers, rolls }
+    }
+
+    /// Maximum number of checkers in this table.
+    #[inline]
+    pub fn checkers(&self) -> u8 {
+        self.checkers
+    }
+
+    /// Probability that `player` needs exactly `k` rolls to bear off all checkers, at index `k`.
+    ///
+    /// The probabilities sum up to `1`. `None` unless all checkers of `player` are in the home
+    /// board and there are at most [OneSidedBearoff::checkers] of them. The opponent's checkers
+    /// don't matter.
+    pub fn rolls_distribution(&self, pos: &Position, player: Player) -> Option<Vec<f32>> {
+        let pos = match player {
+            Player::X => *pos,
+            Player::O => pos.sides_switched(),
+        };
+        if (POINTS + 1..=X_BAR).any(|i| pos.pip(i) > 0) {
+            return None;
+        }
+        let distribution: [u8; POINTS] =
+            std::array::from_fn(|point| pos.pip(point + 1).max(0) as u8);
+        if distribution.iter().sum::<u8>() > self.checkers {
+            return None;
+        }
+        Some(self.rolls[one_sided_index(&distribution)].clone())
+    }
+
+    /// Distribution of the number of rolls for `distribution`.
+    ///
+    /// All distributions with fewer pips must already be stored in `rolls`.
+    fn calculate(distribution: &[u8; POINTS], rolls: &[Vec<f32>]) -> Vec<f32> {
+        if distribution.iter().all(|&c| c == 0) {
+            return vec![1.0];
+        }
+        let expected_rolls = |rolls: &[f32]| -> f32 {
+            rolls
+                .iter()
+                .enumerate()
+                .map(|(k, probability)| k as f32 * probability)
+                .sum()
+        };
+        // A single checker of the opponent which can't interfere with our home board.
+        let pos = position(distribution, &[0, 0, 0, 0, 0, 1]);
+        let mut result = vec![0.0];
+        for (dice, amount) in ALL_21 {
+            let best = pos
+                .all_positions_after_moving(&dice)
+                .iter()
+                .map(|after| {
+                    let (_, own) = distributions(after)
+                        .expect("Bearoff positions can only lead to bearoff positions");
+                    &rolls[one_sided_index(&own)]
+                })
+                .min_by(|a, b| expected_rolls(a).total_cmp(&expected_rolls(b)))
+                .expect("In the home board checkers can always be moved");
+            if result.len() < best.len() + 1 {
+                result.resize(best.len() + 1, 0.0);
+            }
+            for (k, probability) in best.iter().enumerate() {
+                result[k + 1] += probability * amount as f32;
+            }
+        }
+        result.iter().map(|sum| sum / 36.0).collect()
+    }
+}
+
+/// Checkers of `x` and `o` on their home board points, `None` if there are checkers outside.
+/// Index `0` is the one point, index `5` the six point of each player.
+fn distributions(pos: &Position) -> Option<([u8; POINTS], [u8; POINTS])> {
+    let outside_home = (POINTS + 1..=X_BAR).any(|i| pos.pip(i) > 0)
+        || (O_BAR..X_BAR - POINTS).any(|i| pos.pip(i) < 0);
+    if outside_home {
+        return None;
+    }
+    let mut x = [0; POINTS];
+    let mut o = [0; POINTS];
+    for point in 0..POINTS {
+        x[point] = pos.pip(point + 1) as u8;
+        o[point] = (-pos.pip(X_BAR - 1 - point)) as u8;
+    }
+    Some((x, o))
+}
+
+/// Position with player `x` on roll, all other checkers are borne off.
+fn position(x: &[u8; POINTS], o: &[u8; POINTS]) -> Position {
+    let mut pips = [0_i8; 26];
+    for point in 0..POINTS {
+        pips[point + 1] = x[point] as i8;
+        pips[X_BAR - 1 - point] = -(o[point] as i8);
+    }
+    Position::try_from(pips).expect("At most 14 checkers per player")
+}
+
+/// All distributions of up to `checkers` checkers on the home board points.
+fn all_distributions(checkers: u8) -> Vec<[u8; POINTS]> {
+    iter_distributions(checkers, POINTS as u8).collect()
+}
+
+/// Every one-sided bearoff distribution of `checkers` checkers on the lowest `points` points of
+/// the home board. The checkers which are not on a point are borne off.
+///
+/// Index `0` is the ace point, points from `points` on stay empty. There are
+/// `(checkers + points)! / (checkers! * points!)` distributions ("stars and bars").
+/// They are sorted lexicographically, starting with all checkers borne off.
+/// Panics if `points` is bigger than 6.
+pub fn iter_distributions(checkers: u8, points: u8) -> impl Iterator<Item = [u8; POINTS]> {
+    let points = points as usize;
+    assert!(points <= POINTS, "The home board only has 6 points.");
+    std::iter::successors(Some([0; POINTS]), move |previous| {
+        let mut next = *previous;
+        // Like an odometer: increase the last point, if there are no checkers left, reset it
+        // and increase the point before.
+        for point in (0..points).rev() {
+            let on_board: u8 = next.iter().sum();
+            if on_board < checkers {
+                next[point] += 1;
+                return Some(next);
+            }
+            next[point] = 0;
+        }
+        None
+    })
+}
+
+#[inline]
+fn pip_count(distribution: &[u8; POINTS]) -> usize {
+    distribution
+        .iter()
+        .enumerate()
+        .map(|(point, &checkers)| (point + 1) * checkers as usize)
+        .sum()
+}
+
+/// Unique index for each distribution of up to `n` checkers, between `0` and `binomial(n + 6, 6) - 1`.
+///
+/// Think of the checkers as stars and the borders between points as bars ("stars and bars").
+/// The positions of the six bars form a set, which is ranked with the combinatorial number system.
+#[inline]
+fn one_sided_index(distribution: &[u8; POINTS]) -> usize {
+    let mut index = 0;
+    let mut checkers = 0;
+    for (point, &on_point) in distribution.iter().enumerate() {
+        checkers += on_point as usize;
+        // Position of the bar after this point.
+        index += binomial(checkers + point, point + 1);
+    }
+    index
+}
+
+#[inline]
+const fn binomial(n: usize, k: usize) -> usize {
+    if k > n {
+        return 0;
+    }
+    let mut result = 1;
+    let mut i = 0;
+    while i < k {
+        result = result * (n - i) / (i + 1);
+        i += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bearoff::{
+        BearoffDatabase, OneSidedBearoff, all_distributions, binomial, iter_distributions,
+        one_sided_index,
+    };
+    use crate::evaluator::PartialEvaluator;
+    use crate::pos;
+    use crate::position::{Player, Position};
+    use std::collections::HashSet;
+
+    #[test]
+    fn one_sided_index_is_unique_and_dense() {
+        let distributions = all_distributions(4);
+        assert_eq!(distributions.len(), binomial(10, 6));
+        let indices: HashSet<usize> = distributions.iter().map(one_sided_index).collect();
+        assert_eq!(indices.len(), distributions.len());
+        assert_eq!(*indices.iter().max().unwrap(), distributions.len() - 1);
+    }
+
+    #[test]
+    fn iter_distributions_count_is_stars_and_bars() {
+        for checkers in 0..=6 {
+            for points in 0..=6 {
+                let count = iter_distributions(checkers, points).count();
+                assert_eq!(
+                    count,
+                    binomial(checkers as usize + points as usize, points as usize)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn iter_distributions_of_two_checkers_on_two_points() {
+        let distributions: Vec<[u8; 6]> = iter_distributions(2, 2).collect();
+        assert_eq!(
+            distributions,
+            vec![
+                [0, 0, 0, 0, 0, 0],
+                [0, 1, 0, 0, 0, 0],
+                [0, 2, 0, 0, 0, 0],
+                [1, 0, 0, 0, 0, 0],
+                [1, 1, 0, 0, 0, 0],
+                [2, 0, 0, 0, 0, 0],
+            ]
+        );
+    }
+
+    #[test]
+    fn single_checkers() {
+        let db = BearoffDatabase::generate(2);
+        // Every roll bears off the last checker.
+        let probabilities = db.try_eval(&pos!(x 1:1; o 24:1)).unwrap();
+        assert_eq!(probabilities.win_normal, 1.0);
+        assert_eq!(probabilities.lose_normal, 0.0);
+        // 11, 21, 31, 41 and 32 don't bear off from the six point: 9 of 36 rolls.
+        let probabilities = db.try_eval(&pos!(x 6:1; o 24:1)).unwrap();
+        assert_eq!(probabilities.win_normal, 0.75);
+        assert_eq!(probabilities.lose_normal, 0.25);
+        assert_eq!(probabilities.win_gammon, 0.0);
+        assert_eq!(probabilities.lose_gammon, 0.0);
+    }
+
+    #[test]
+    fn rolls_distribution_of_single_checkers() {
+        let table = OneSidedBearoff::generate(3);
+        let pos = pos!(x 1:1; o 19:1);
+        assert_eq!(
+            table.rolls_distribution(&pos, Player::X),
+            Some(vec![0.0, 1.0])
+        );
+        // 11, 21, 31, 41 and 32 don't bear off from the six point: 9 of 36 rolls.
+        assert_eq!(
+            table.rolls_distribution(&pos, Player::O),
+            Some(vec![0.0, 0.75, 0.25])
+        );
+    }
+
+    #[test]
+    fn rolls_distribution_sums_to_one() {
+        let table = OneSidedBearoff::generate(4);
+        for pos in [pos!(x 6:4; o 24:1), pos!(x 5:1, 3:2, 1:1; o 19:2)] {
+            let rolls = table.rolls_distribution(&pos, Player::X).unwrap();
+            assert_eq!(rolls[0], 0.0);
+            assert!((rolls.iter().sum::<f32>() - 1.0).abs() < 0.0001);
+        }
+        // Checkers outside of the home board or too many checkers
+        assert!(
+            table
+                .rolls_distribution(&pos!(x 7:1; o 24:1), Player::X)
+                .is_none()
+        );
+        assert!(
+            table
+                .rolls_distribution(&pos!(x 1:5; o 24:1), Player::X)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn positions_outside_of_database() {
+        let db = BearoffDatabase::generate(2);
+        // Checker outside of home board
+        assert!(db.try_eval(&pos!(x 7:1; o 24:1)).is_none());
+        assert!(db.try_eval(&pos!(x 1:1; o 18:1)).is_none());
+        // Too many checkers
+        assert!(db.try_eval(&pos!(x 1:3; o 24:1)).is_none());
+        assert!(db.try_eval(&pos!(x 1:1; o 24:2, 23:1)).is_none());
+        // Game over
+        assert!(db.try_eval(&pos!(x 1:1; o)).is_none());
+    }
+
+    #[test]
+    fn symmetric_positions_favor_player_on_roll() {
+        let db = BearoffDatabase::generate(3);
+        let pos = pos!(x 5:1, 3:2; o 20:1, 22:2);
+        let win = db.try_eval(&pos).unwrap().win_normal;
+        assert!(win > 0.5);
+        assert!(win < 1.0);
+    }
+
+    #[test]
+    fn mmap_and_in_memory_lookups_are_identical() {
+        // Given
+        let db = BearoffDatabase::generate(3);
+        let path = std::env::temp_dir().join(format!("wildbg-bearoff-{}.db", std::process::id()));
+        db.save(&path).unwrap();
+        // When
+        let in_memory = BearoffDatabase::open(&path).unwrap();
+        let mapped = BearoffDatabase::open_mmap(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        // Then
+        assert_eq!(mapped.checkers(), 3);
+        let sampled: [Position; 4] = [
+            pos!(x 6:3; o 19:3),
+            pos!(x 1:1, 2:1; o 24:1, 23:1, 19:1),
+            pos!(x 4:2; o 21:1),
+            pos!(x 3:1; o 20:2, 19:1),
+        ];
+        for pos in sampled {
+            let expected = db.try_eval(&pos).unwrap();
+            assert_eq!(in_memory.try_eval(&pos), Some(expected.clone()));
+            assert_eq!(mapped.try_eval(&pos), Some(expected));
+        }
+    }
+
+    #[test]
+    fn open_rejects_other_files() {
+        let path =
+            std::env::temp_dir().join(format!("wildbg-no-bearoff-{}.db", std::process::id()));
+        std::fs::write(&path, b"not a database").unwrap();
+        let result = BearoffDatabase::open_mmap(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(result.is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::evaluator::{BatchEvaluator, Evaluator};
+use crate::evaluator::{BatchEvaluator, Evaluator, EvaluatorInfo};
 use crate::inputs::{ContactInputsGen, RaceInputsGen};
 use crate::onnx::OnnxEvaluator;
 use crate::position::{GamePhase, OngoingPhase, Position};
@@ -49,6 +49,11 @@ impl BatchEvaluator for CompositeEvaluator {
 
         game_over
     }
+
+    /// The same as the neural nets for contact and race.
+    fn batch_info(&self) -> EvaluatorInfo {
+        self.contact_evaluator.batch_info()
+    }
 }
 
 impl CompositeEvaluator {
@@ -25,6 +25,9 @@ pub static ALL_441: [([Dice; 2], usize); 441] = Dice::all_441();
 pub static ALL_21: [(Dice, usize); 21] = Dice::all_21();
 
 impl Dice {
+    /// Only use this for values known to be legal, for example constants.
+    ///
+    /// Values outside of `1..=6` are only caught in debug builds. Use `try_new` for other input.
     #[inline]
     pub const fn new(die1: usize, die2: usize) -> Self {
         debug_assert!(die1 > 0);
@@ -38,6 +41,31 @@ impl Dice {
         }
     }
 
+    /// Returns `None` unless both values are between 1 and 6.
+    #[inline]
+    pub const fn try_new(die1: usize, die2: usize) -> Option<Self> {
+        if die1 < 1 || die1 > 6 || die2 < 1 || die2 > 6 {
+            None
+        } else {
+            Some(Dice::new(die1, die2))
+        }
+    }
+
+    /// `true` for doubles like 3-3, which are played four times.
+    #[inline]
+    pub fn is_double(&self) -> bool {
+        matches!(self, Dice::Double(_))
+    }
+
+    /// Total number of pips of the roll, doubles count four times: 3-3 are 12 pips.
+    #[inline]
+    pub fn pips(&self) -> u32 {
+        match self {
+            Dice::Double(die) => 4 * *die as u32,
+            Dice::Mixed(dice) => (dice.big + dice.small) as u32,
+        }
+    }
+
     /// 36 Dice, all double moves appear once, all mixed moves appear twice
     pub const fn all_36() -> [Dice; 36] {
         let mut all_36 = [Dice::Double(1); 36]; // Dummy values will be replaced
@@ -126,11 +154,7 @@ impl TryFrom<(usize, usize)> for Dice {
     type Error = &'static str;
 
     fn try_from(value: (usize, usize)) -> Result<Self, Self::Error> {
-        if value.0 < 1 || value.0 > 6 || value.1 < 1 || value.1 > 6 {
-            Err("Dice values must be between 1 and 6.")
-        } else {
-            Ok(Dice::new(value.0, value.1))
-        }
+        Dice::try_new(value.0, value.1).ok_or("Dice values must be between 1 and 6.")
     }
 }
 
@@ -174,6 +198,20 @@ mod dice_tests {
         }
     }
 
+    #[test]
+    fn double_with_pips() {
+        let dice = Dice::new(3, 3);
+        assert!(dice.is_double());
+        assert_eq!(dice.pips(), 12);
+    }
+
+    #[test]
+    fn mixed_with_pips() {
+        let dice = Dice::new(6, 1);
+        assert!(!dice.is_double());
+        assert_eq!(dice.pips(), 7);
+    }
+
     #[test]
     fn all_441() {
         let all_441 = ALL_441;
@@ -202,4 +240,27 @@ mod dice_tests {
         }
         assert_eq!(dice_set.len(), 441);
     }
+
+    #[test]
+    fn try_new_rejects_out_of_range_values() {
+        assert_eq!(Dice::try_new(7, 0), None);
+        assert_eq!(Dice::try_new(0, 3), None);
+        assert_eq!(Dice::try_new(3, 7), None);
+        assert_eq!(Dice::try_new(0, 0), None);
+    }
+
+    #[test]
+    fn try_new_accepts_all_legal_values() {
+        for die1 in 1..=6 {
+            for die2 in 1..=6 {
+                assert_eq!(Dice::try_new(die1, die2), Some(Dice::new(die1, die2)));
+            }
+        }
+    }
+
+    #[test]
+    fn try_from_rejects_out_of_range_values() {
+        assert!(Dice::try_from((7, 0)).is_err());
+        assert_eq!(Dice::try_from((2, 5)), Ok(Dice::new(5, 2)));
+    }
 }
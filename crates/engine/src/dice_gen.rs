@@ -14,7 +14,7 @@ pub trait DiceGen {
         // Also this works for all implementing types.
         loop {
             let dice = self.roll();
-            if let Dice::Mixed(_) = dice {
+            if !dice.is_double() {
                 return dice;
             }
         }
@@ -57,12 +57,50 @@ impl FastrandDice {
         self.generator.u64(..)
     }
 
+    /// The complete state of the random number generator, for example to replay a game exactly.
+    ///
+    /// Unlike [FastrandDice::seed], this doesn't change the state. A generator created with
+    /// [FastrandDice::from_state] rolls the same dice from here on.
+    pub fn state(&self) -> u64 {
+        self.generator.get_seed()
+    }
+
+    /// Restores a generator from a state returned by [FastrandDice::state].
+    pub fn from_state(state: u64) -> FastrandDice {
+        FastrandDice {
+            generator: fastrand::Rng::with_seed(state),
+        }
+    }
+
     /// Returns a random seed, not depending on state. Don't use it in tests or benchmarks.
     pub fn random_seed() -> u64 {
         fastrand::u64(..)
     }
 }
 
+/// Rolls the complement of another dice generator: each die `d` becomes `7 - d`.
+///
+/// A game played with these dice is the antithetic "mirror" of the game played with the original
+/// generator: big rolls become small rolls and vice versa.
+pub struct ComplementedDice<T: DiceGen> {
+    dice_gen: T,
+}
+
+impl<T: DiceGen> DiceGen for ComplementedDice<T> {
+    fn roll(&mut self) -> Dice {
+        match self.dice_gen.roll() {
+            Dice::Double(die) => Dice::new(7 - die, 7 - die),
+            Dice::Mixed(dice) => Dice::new(7 - dice.big(), 7 - dice.small()),
+        }
+    }
+}
+
+impl<T: DiceGen> ComplementedDice<T> {
+    pub fn new(dice_gen: T) -> Self {
+        Self { dice_gen }
+    }
+}
+
 /// Use this for unit tests where you want to control the dice.
 pub struct DiceGenMock {
     dice: Vec<Dice>,
@@ -157,6 +195,23 @@ mod fastrand_dice_tests {
         assert_ne!(dice_gen1.roll(), dice_gen2.roll());
     }
 
+    #[test]
+    fn restoring_state_reproduces_next_rolls() {
+        // Given
+        let mut dice_gen = FastrandDice::with_seed(42);
+        for _ in 0..5 {
+            dice_gen.roll();
+        }
+        let state = dice_gen.state();
+        assert_eq!(dice_gen.state(), state);
+        // When
+        let mut restored = FastrandDice::from_state(state);
+        // Then
+        for _ in 0..20 {
+            assert_eq!(restored.roll(), dice_gen.roll());
+        }
+    }
+
     /// Make sure that we have the same behavior across different library versions.
     #[test]
     fn deterministic_behavior() {
@@ -205,3 +260,18 @@ mod dice_gen_mock_tests {
         dice_gen.roll();
     }
 }
+
+#[cfg(test)]
+mod complemented_dice_tests {
+    use crate::dice::Dice;
+    use crate::dice_gen::{ComplementedDice, DiceGen, DiceGenMock};
+
+    #[test]
+    fn rolls_complements() {
+        let mock = DiceGenMock::new(&[Dice::new(6, 1), Dice::new(5, 3), Dice::new(2, 2)]);
+        let mut dice_gen = ComplementedDice::new(mock);
+        assert_eq!(dice_gen.roll(), Dice::new(1, 6));
+        assert_eq!(dice_gen.roll(), Dice::new(2, 4));
+        assert_eq!(dice_gen.roll(), Dice::new(5, 5));
+    }
+}
@@ -1,6 +1,9 @@
-use crate::dice::Dice;
-use crate::position::Position;
-use crate::probabilities::Probabilities;
+use crate::dice::{ALL_21, Dice};
+use crate::position::GameState::{GameOver, Ongoing};
+use crate::position::{Position, STARTING, X_BAR};
+use crate::probabilities::{Probabilities, cmp_nan_lowest};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 use std::collections::HashMap;
 
 /// [Evaluator] is one of the central parts of the engine. Implementing structs only have to
@@ -29,6 +32,62 @@ pub trait Evaluator {
             .collect()
     }
 
+    /// Like [Evaluator::eval_batch], but borrows the positions, for example from a reused buffer.
+    ///
+    /// Evaluators which implement [BatchEvaluator] get a copy of the positions.
+    fn eval_slice(&self, positions: &[Position]) -> Vec<(Position, Probabilities)> {
+        positions.iter().map(|pos| (*pos, self.eval(pos))).collect()
+    }
+
+    /// Name and nominal strength of the evaluator, for example to show it in a user interface.
+    ///
+    /// By default the type name, evaluating without looking ahead. Wrappers should forward the
+    /// info of the evaluator they wrap.
+    fn info(&self) -> EvaluatorInfo {
+        EvaluatorInfo {
+            name: std::any::type_name::<Self>(),
+            approx_ply: 0,
+            is_rollout: false,
+        }
+    }
+
+    /// Probabilities for player `x` who is on roll. The same as [Evaluator::eval], the name only
+    /// makes it explicit where [Evaluator::eval_not_on_roll] is used as well.
+    #[inline]
+    fn eval_on_roll(&self, pos: &Position) -> Probabilities {
+        self.eval(pos)
+    }
+
+    /// Probabilities for player `x` if it were `o`'s turn to roll, for example for a player
+    /// deciding about a double before the opponent rolls.
+    ///
+    /// This evaluates [Position::sides_switched] and switches the result back with
+    /// [Probabilities::switch_sides], so callers don't have to flip twice.
+    #[inline]
+    fn eval_not_on_roll(&self, pos: &Position) -> Probabilities {
+        self.eval(&pos.sides_switched()).switch_sides()
+    }
+
+    /// Evaluates `pos` twice: once with `x` on roll and once with `o` on roll.
+    ///
+    /// Both results are from the perspective of player `x`, so they can be compared directly,
+    /// for example to see how much being on roll is worth when studying cube ownership.
+    fn eval_both_sides(&self, pos: &Position) -> (Probabilities, Probabilities) {
+        (self.eval_on_roll(pos), self.eval_not_on_roll(pos))
+    }
+
+    /// Cubeless equity of `pos` for player `x`, the same as `self.eval(pos).equity()`.
+    #[inline]
+    fn equity(&self, pos: &Position) -> f32 {
+        self.eval(pos).equity()
+    }
+
+    /// Probability that player `x` wins `pos`, including gammons and backgammons.
+    #[inline]
+    fn win_probability(&self, pos: &Position) -> f32 {
+        self.eval(pos).win()
+    }
+
     /// Returns the position after applying the *best* move by equity to `pos`.
     /// The returned `Position` has already switched sides.
     /// This means the returned position will have the *lowest* equity of possible positions.
@@ -51,25 +110,59 @@ pub trait Evaluator {
     where
         F: Fn(&Probabilities) -> f32,
     {
-        let mut positions = pos.all_positions_after_moving(dice);
+        let positions = pos.all_positions_after_moving(dice);
+        match forced_position(&positions) {
+            Some(position) => position,
+            None => best_of_positions(self, &positions, value),
+        }
+    }
 
-        // Two optimizations so that we don't have to call eval_batch that often.
-        // The function would also work without the next 6 lines.
-        if positions.len() == 1 {
-            return positions.pop().unwrap();
+    /// Completes a half played move: `pos` is the position after playing one die, the best move
+    /// with `remaining_die` is applied. The returned `Position` has already switched sides.
+    ///
+    /// If `remaining_die` can't be played, only the sides are switched.
+    fn best_completion(&self, pos: &Position, remaining_die: usize) -> Position {
+        let positions: Vec<Position> = (1..=X_BAR)
+            .filter_map(|from| pos.try_move_single_checker(from, remaining_die))
+            .map(|position| position.sides_switched())
+            .collect();
+        if positions.is_empty() {
+            return pos.sides_switched();
         }
-        if let Some(end_of_game) = positions.iter().position(|p| p.has_lost()) {
-            return positions.swap_remove(end_of_game);
+        match forced_position(&positions) {
+            Some(position) => position,
+            None => best_of_positions(self, &positions, |probabilities| probabilities.equity()),
+        }
+    }
+
+    /// The best move by equity for each pair of position and dice, in the same order as `inputs`.
+    ///
+    /// Each result is the same as [Evaluator::best_position_by_equity], so it has already switched sides.
+    /// With the feature `rayon`, the inputs are evaluated in parallel.
+    fn best_moves_batch(&self, inputs: &[(Position, Dice)]) -> Vec<Position>
+    where
+        Self: MaybeSync,
+    {
+        maybe_par_iter(inputs)
+            .map(|(pos, dice)| self.best_position_by_equity(pos, dice))
+            .collect()
+    }
+
+    /// Like [Evaluator::best_position_by_equity], but the legal moves are generated into `buffer`.
+    ///
+    /// Reusing the same buffer for many moves, for example during a rollout, saves allocations.
+    /// Forced moves and moves ending the game are found without allocating at all.
+    fn best_position_by_equity_reusing(
+        &self,
+        pos: &Position,
+        dice: &Dice,
+        buffer: &mut Vec<Position>,
+    ) -> Position {
+        pos.all_positions_after_moving_into(dice, buffer);
+        match forced_position(buffer) {
+            Some(position) => position,
+            None => best_of_positions(self, buffer, |p| p.equity()),
         }
-        self.eval_batch(positions)
-            .into_iter()
-            // Negate so that we pick the position with the *highest* value for `x`
-            // while keeping `min_by`'s "first among equals" tie-breaking, which
-            // keeps move selection deterministic and unchanged for equity/win.
-            .map(|(position, probabilities)| (position, -value(&probabilities.switch_sides())))
-            .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-            .unwrap()
-            .0
     }
 
     /// All legal positions after moving with the given dice.
@@ -81,7 +174,9 @@ pub trait Evaluator {
         position: &Position,
         dice: &Dice,
     ) -> Vec<(Position, Probabilities)> {
-        self.positions_and_probabilities(position, dice, |probabilities| probabilities.equity())
+        let mut pos_and_probs = positions_and_probabilities_unsorted(self, position, dice);
+        pos_and_probs.sort_unstable_by(|(_, prob_a), (_, prob_b)| prob_b.cmp_by_equity(prob_a));
+        pos_and_probs
     }
 
     /// All legal positions after moving with the given dice.
@@ -98,16 +193,246 @@ pub trait Evaluator {
     where
         F: Fn(&Probabilities) -> f32,
     {
-        let after_moving = position.all_positions_after_moving(dice);
-        let mut pos_and_probs: Vec<(Position, Probabilities)> = self
-            .eval_batch(after_moving)
+        let mut pos_and_probs = positions_and_probabilities_unsorted(self, position, dice);
+        pos_and_probs.sort_unstable_by(|(_, prob_a), (_, prob_b)| {
+            cmp_nan_lowest(value(prob_b), value(prob_a))
+        });
+        pos_and_probs
+    }
+
+    /// Groups the legal moves with `dice` into clusters of essentially equal moves.
+    ///
+    /// The moves are sorted by equity, best first. A cluster starts with its best move and contains
+    /// all following moves whose equity is at most `epsilon` worse than that. The positions are
+    /// from the perspective of player `x` who has moved, like in [Evaluator::positions_and_probabilities_by_equity].
+    fn move_clusters(&self, pos: &Position, dice: &Dice, epsilon: f32) -> Vec<Vec<Position>> {
+        let mut clusters: Vec<Vec<Position>> = Vec::new();
+        let mut cluster_equity = f32::NAN;
+        for (position, probabilities) in self.positions_and_probabilities_by_equity(pos, dice) {
+            let equity = probabilities.equity();
+            match clusters.last_mut() {
+                Some(cluster) if cluster_equity - equity <= epsilon => cluster.push(position),
+                _ => {
+                    clusters.push(vec![position]);
+                    cluster_equity = equity;
+                }
+            }
+        }
+        clusters
+    }
+
+    /// All legal positions after moving with `dice` whose equity lies between `min_equity` and
+    /// `max_equity`, both inclusive. Sorted, the best position is first.
+    ///
+    /// Useful for puzzles: a band with a single position means there is a unique correct play.
+    /// The positions are from the perspective of player `x` who has moved, like in [Evaluator::positions_and_probabilities_by_equity].
+    fn find_positions_with_equity(
+        &self,
+        pos: &Position,
+        dice: &Dice,
+        min_equity: f32,
+        max_equity: f32,
+    ) -> Vec<Position> {
+        self.positions_and_probabilities_by_equity(pos, dice)
             .into_iter()
-            .map(|(pos, probabilities)| (pos.sides_switched(), probabilities.switch_sides()))
+            .filter(|(_, probabilities)| {
+                (min_equity..=max_equity).contains(&probabilities.equity())
+            })
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// The equity of the opponent's best reply for each of the 21 distinct rolls.
+    ///
+    /// `pos_after_move` is the position after our move. Like the positions returned by
+    /// [Position::all_positions_after_moving] it has already switched sides, so the opponent is `x`.
+    /// The equities are from the perspective of the opponent, so high values are bad for us.
+    fn reply_equities(&self, pos_after_move: &Position) -> Vec<(Dice, f32)> {
+        ALL_21
+            .iter()
+            .map(|(dice, _)| {
+                let best_reply = self.positions_and_probabilities_by_equity(pos_after_move, dice);
+                (*dice, best_reply[0].1.equity())
+            })
+            .collect()
+    }
+
+    /// How lucky player `x` was to roll `dice` in `pos`.
+    ///
+    /// This is the equity after the best move with `dice` minus the average equity after the best
+    /// move over all 36 rolls. Positive values mean `x` rolled better than average.
+    fn roll_luck(&self, pos: &Position, dice: &Dice) -> f32 {
+        let equity_after = |dice: &Dice| {
+            self.positions_and_probabilities_by_equity(pos, dice)[0]
+                .1
+                .equity()
+        };
+        let average = ALL_21
+            .iter()
+            .map(|(dice, amount)| equity_after(dice) * *amount as f32)
+            .sum::<f32>()
+            / 36.0;
+        equity_after(dice) - average
+    }
+
+    /// Standard deviation of the equity after the best move of `x`, weighted over all 36 rolls.
+    ///
+    /// A measure for how much the equity can swing with the next roll. High volatility close to
+    /// the double point means doubling now is better than waiting.
+    fn next_roll_volatility(&self, pos: &Position) -> f32 {
+        let equities: Vec<(f32, f32)> = ALL_21
+            .iter()
+            .map(|(dice, amount)| {
+                let equity = self.positions_and_probabilities_by_equity(pos, dice)[0]
+                    .1
+                    .equity();
+                (equity, *amount as f32 / 36.0)
+            })
             .collect();
-        pos_and_probs
-            .sort_unstable_by(|(_, prob_a), (_, prob_b)| value(prob_b).total_cmp(&value(prob_a)));
-        pos_and_probs
+        let mean: f32 = equities
+            .iter()
+            .map(|(equity, weight)| equity * weight)
+            .sum();
+        let variance: f32 = equities
+            .iter()
+            .map(|(equity, weight)| (equity - mean).powi(2) * weight)
+            .sum();
+        variance.sqrt()
     }
+
+    /// The probabilities after the best opening move from [STARTING] for each of the 15 opening rolls.
+    ///
+    /// Doubles can't be opening rolls, so they are left out. The probabilities are from the
+    /// perspective of the player who moves first, in the same order as [ALL_21].
+    fn opening_equities(&self) -> Vec<(Dice, Probabilities)> {
+        ALL_21
+            .iter()
+            .filter(|(dice, _)| !dice.is_double())
+            .map(|(dice, _)| {
+                let (_, probabilities) = self
+                    .positions_and_probabilities_by_equity(&STARTING, dice)
+                    .swap_remove(0);
+                (*dice, probabilities)
+            })
+            .collect()
+    }
+
+    /// How much the pip count lead of `x` changes by playing the best move with `dice`.
+    ///
+    /// The lead is the pip count of `o` minus the pip count of `x`. In a race this is just the
+    /// number of pips moved, hitting also adds the pips the hit checker is sent back. A cheap
+    /// proxy for the quality of a move in races.
+    fn pip_swing(&self, pos: &Position, dice: &Dice) -> i32 {
+        let (x_before, o_before) = pos.pip_counts();
+        // The position after moving has switched sides.
+        let (o_after, x_after) = self.best_position_by_equity(pos, dice).pip_counts();
+        let lead_before = o_before as i32 - x_before as i32;
+        let lead_after = o_after as i32 - x_after as i32;
+        lead_after - lead_before
+    }
+
+    /// Fraction of the 1296 sequences of our roll and the opponent's reply after which we are
+    /// on roll again and the opponent would pass a double: the "market losers" of `pos`.
+    ///
+    /// Many market losers mean a double now is better than waiting. Sequences in which the game
+    /// ends don't count. This is a cubeless estimate, see [MARKET_LOST_EQUITY].
+    fn market_losers(&self, pos: &Position) -> f32 {
+        let mut market_losers = 0;
+        for (dice, amount) in ALL_21.iter() {
+            let after_move = self.best_position_by_equity(pos, dice);
+            if after_move.is_game_over() {
+                continue;
+            }
+            for (reply_dice, reply_amount) in ALL_21.iter() {
+                let after_reply = self.best_position_by_equity(&after_move, reply_dice);
+                if !after_reply.is_game_over() && self.equity(&after_reply) > MARKET_LOST_EQUITY {
+                    market_losers += amount * reply_amount;
+                }
+            }
+        }
+        market_losers as f32 / 1296.0
+    }
+}
+
+/// Self-report of an [Evaluator], see [Evaluator::info].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvaluatorInfo {
+    pub name: &'static str,
+    /// How many plies the evaluator roughly looks ahead, `0` for a static evaluation.
+    pub approx_ply: u8,
+    pub is_rollout: bool,
+}
+
+/// Cubeless equity above which the opponent should pass a double in a money game.
+///
+/// After taking, the opponent loses twice our equity. Passing costs one point, so a take is
+/// only correct while our equity is at most `0.5`, which is the well-known 25% take point
+/// without gammons.
+pub const MARKET_LOST_EQUITY: f32 = 0.5;
+
+/// `Sync` with the feature `rayon`, because then [Evaluator::best_moves_batch] shares the evaluator
+/// between threads. Without the feature, every type is `MaybeSync`.
+#[cfg(feature = "rayon")]
+pub trait MaybeSync: Sync {}
+#[cfg(feature = "rayon")]
+impl<T: Sync + ?Sized> MaybeSync for T {}
+
+#[cfg(not(feature = "rayon"))]
+pub trait MaybeSync {}
+#[cfg(not(feature = "rayon"))]
+impl<T: ?Sized> MaybeSync for T {}
+
+/// Iterates over `items` in parallel with the feature `rayon`, otherwise one after another.
+#[cfg(feature = "rayon")]
+fn maybe_par_iter<T: Sync>(items: &[T]) -> rayon::slice::Iter<'_, T> {
+    items.par_iter()
+}
+
+#[cfg(not(feature = "rayon"))]
+fn maybe_par_iter<T>(items: &[T]) -> std::slice::Iter<'_, T> {
+    items.iter()
+}
+
+/// Two optimizations so that we don't have to call eval_batch that often: If there is only one
+/// legal move or a move ends the game, that's the best move.
+/// [Evaluator::best_position] would also work without this.
+fn forced_position(positions: &[Position]) -> Option<Position> {
+    if positions.len() == 1 {
+        return Some(positions[0]);
+    }
+    positions.iter().find(|p| p.has_lost()).copied()
+}
+
+fn best_of_positions<E, F>(evaluator: &E, positions: &[Position], value: F) -> Position
+where
+    E: Evaluator + ?Sized,
+    F: Fn(&Probabilities) -> f32,
+{
+    evaluator
+        .eval_slice(positions)
+        .into_iter()
+        .map(|(position, probabilities)| (position, value(&probabilities.switch_sides())))
+        // Reverse the comparison so that we pick the position with the *highest* value for `x`
+        // while keeping `min_by`'s "first among equals" tie-breaking, which
+        // keeps move selection deterministic and unchanged for equity/win.
+        // A `NaN` value is never picked, unless all values are `NaN`.
+        .min_by(|a, b| cmp_nan_lowest(b.1, a.1))
+        .unwrap()
+        .0
+}
+
+/// All legal positions after moving with the given dice, in the order of move generation.
+/// Positions and probabilities are from the perspective of player `x` who has to move.
+fn positions_and_probabilities_unsorted<E: Evaluator + ?Sized>(
+    evaluator: &E,
+    position: &Position,
+    dice: &Dice,
+) -> Vec<(Position, Probabilities)> {
+    evaluator
+        .eval_batch(position.all_positions_after_moving(dice))
+        .into_iter()
+        .map(|(pos, probabilities)| (pos.sides_switched(), probabilities.switch_sides()))
+        .collect()
 }
 
 /// [BatchEvaluator] is a subtrait of [Evaluator]. The function [Evaluator::eval_batch] is
@@ -121,6 +446,15 @@ pub trait Evaluator {
 pub trait BatchEvaluator: Evaluator {
     /// Evaluate all legal moves following a certain position/dice combination.
     fn eval_positions(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)>;
+
+    /// Returned by [Evaluator::info], which can't be overridden because of the blanket implementation.
+    fn batch_info(&self) -> EvaluatorInfo {
+        EvaluatorInfo {
+            name: std::any::type_name::<Self>(),
+            approx_ply: 0,
+            is_rollout: false,
+        }
+    }
 }
 
 impl<T: BatchEvaluator> Evaluator for T {
@@ -136,6 +470,65 @@ impl<T: BatchEvaluator> Evaluator for T {
     fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
         BatchEvaluator::eval_positions(self, positions)
     }
+
+    #[inline]
+    fn eval_slice(&self, positions: &[Position]) -> Vec<(Position, Probabilities)> {
+        BatchEvaluator::eval_positions(self, positions.to_vec())
+    }
+
+    fn info(&self) -> EvaluatorInfo {
+        self.batch_info()
+    }
+}
+
+/// [PartialEvaluator] is for evaluators which can only evaluate some positions,
+/// for example a bearoff database which only knows positions without contact.
+pub trait PartialEvaluator {
+    /// Returns a cubeless evaluation of a position or `None` if this position can't be evaluated.
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities>;
+
+    /// Chains two partial evaluators: `other` is only asked if `self` can't evaluate a position.
+    ///
+    /// Several evaluators can be chained like `bearoff.or_else(race).or_else(...)`.
+    fn or_else<P: PartialEvaluator>(self, other: P) -> OrElse<Self, P>
+    where
+        Self: Sized,
+    {
+        OrElse {
+            first: self,
+            second: other,
+        }
+    }
+}
+
+/// Two chained partial evaluators, see [PartialEvaluator::or_else].
+pub struct OrElse<A: PartialEvaluator, B: PartialEvaluator> {
+    first: A,
+    second: B,
+}
+
+impl<A: PartialEvaluator, B: PartialEvaluator> PartialEvaluator for OrElse<A, B> {
+    /// The evaluation of the first evaluator which can evaluate `pos`.
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+        self.first
+            .try_eval(pos)
+            .or_else(|| self.second.try_eval(pos))
+    }
+}
+
+/// Exact results of positions in which the game is already over, `None` for all other positions.
+///
+/// Neural nets are only trained on ongoing games, so this belongs at the beginning of a chain like
+/// `TerminalEvaluator.or_else(bearoff)`.
+pub struct TerminalEvaluator;
+
+impl PartialEvaluator for TerminalEvaluator {
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+        match pos.game_state() {
+            GameOver(result) => Some(Probabilities::from(result)),
+            Ongoing => None,
+        }
+    }
 }
 
 pub struct RandomEvaluator {}
@@ -205,9 +598,13 @@ impl EvaluatorFake {
 #[cfg(test)]
 mod evaluator_trait_tests {
     use crate::dice::Dice;
-    use crate::evaluator::{Evaluator, EvaluatorFake};
+    use crate::evaluator::{Evaluator, EvaluatorFake, PartialEvaluator, TerminalEvaluator};
     use crate::pos;
-    use crate::position::Position;
+    use crate::position::GameResult::{LoseBg, LoseGammon, LoseNormal};
+    use crate::position::{Position, STARTING};
+    use crate::probabilities::Probabilities;
+    use crate::trivial_endgame::TrivialEndgame;
+    use std::cell::Cell;
 
     fn position_with_lowest_equity() -> Position {
         pos!(x 5:1, 3:1; o 20:2).sides_switched()
@@ -223,6 +620,354 @@ mod evaluator_trait_tests {
         fake
     }
 
+    /// Partial evaluator which can only evaluate a single position and counts how often it is asked.
+    struct OnlyOne {
+        position: Position,
+        calls: Cell<usize>,
+    }
+
+    impl OnlyOne {
+        fn new(position: Position) -> Self {
+            Self {
+                position,
+                calls: Cell::new(0),
+            }
+        }
+    }
+
+    impl PartialEvaluator for &OnlyOne {
+        fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+            self.calls.set(self.calls.get() + 1);
+            (*pos == self.position).then(|| [1.0, 0.0, 0.0, 0.0, 0.0, 0.0].into())
+        }
+    }
+
+    /// Exact evaluation of races with a single checker for each player.
+    struct SingleCheckerRace(TrivialEndgame);
+
+    impl Evaluator for SingleCheckerRace {
+        fn eval(&self, pos: &Position) -> Probabilities {
+            self.0.try_eval(pos).unwrap()
+        }
+    }
+
+    #[test]
+    fn gammon_go_value_prefers_more_gammons_than_equity() {
+        // Given
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        let gammonish = position_with_lowest_equity();
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        // All moves win 80% safely, except for `gammonish` with 55% wins and 35% gammons.
+        let mut evaluator = EvaluatorFake::with_default([0.2, 0.0, 0.0, 0.8, 0.0, 0.0].into());
+        evaluator.insert(gammonish, [0.45, 0.0, 0.0, 0.2, 0.35, 0.0].into());
+        // When
+        let by_equity = evaluator.best_position_by_equity(&pos, &dice);
+        let gammon_go = evaluator.best_position(&pos, &dice, Probabilities::gammon_go_value);
+        // Then
+        assert_ne!(by_equity, gammonish);
+        assert_eq!(gammon_go, gammonish);
+    }
+
+    #[test]
+    fn equity_and_win_probability_are_shortcuts_for_eval() {
+        let evaluator = evaluator_fake();
+        let pos = pos!(x 7:2; o 20:2);
+        assert_eq!(evaluator.equity(&pos), evaluator.eval(&pos).equity());
+        assert_eq!(evaluator.win_probability(&pos), evaluator.eval(&pos).win());
+    }
+
+    #[test]
+    fn or_else_stops_at_first_evaluator_with_result() {
+        // Given
+        let race = pos!(x 6:1; o 19:1);
+        let other = pos!(x 5:1; o 19:1);
+        let first = OnlyOne::new(race);
+        let second = OnlyOne::new(other);
+        let chained = (&first).or_else(&second);
+        // When
+        let race_result = chained.try_eval(&race);
+        // Then
+        assert!(race_result.is_some());
+        assert_eq!(first.calls.get(), 1);
+        assert_eq!(second.calls.get(), 0);
+        // When
+        let other_result = chained.try_eval(&other);
+        let unknown_result = chained.try_eval(&pos!(x 4:1; o 19:1));
+        // Then
+        assert!(other_result.is_some());
+        assert!(unknown_result.is_none());
+        assert_eq!(first.calls.get(), 3);
+        assert_eq!(second.calls.get(), 2);
+    }
+
+    #[test]
+    fn terminal_evaluator_returns_each_result_exactly() {
+        let terminal = TerminalEvaluator;
+        let lost = [
+            (pos!(x 19:14; o), LoseNormal),
+            (pos!(x 18:15; o), LoseGammon),
+            (pos!(x 19:15; o), LoseBg),
+        ];
+        for (pos, result) in lost {
+            let probabilities = terminal.try_eval(&pos).unwrap();
+            assert_eq!(probabilities, Probabilities::from(result.clone()));
+            let switched = terminal.try_eval(&pos.sides_switched()).unwrap();
+            assert_eq!(switched, Probabilities::from(result.reverse()));
+        }
+        assert_eq!(
+            terminal.try_eval(&pos!(x 19:14; o)).unwrap().lose_normal,
+            1.0
+        );
+        assert_eq!(
+            terminal
+                .try_eval(&pos!(x 19:15; o).sides_switched())
+                .unwrap()
+                .win_bg,
+            1.0
+        );
+    }
+
+    #[test]
+    fn terminal_evaluator_ignores_ongoing_games() {
+        assert_eq!(TerminalEvaluator.try_eval(&STARTING), None);
+        assert_eq!(TerminalEvaluator.try_eval(&pos!(x 1:1; o 24:1)), None);
+    }
+
+    #[test]
+    fn best_moves_batch_same_as_one_at_a_time() {
+        // Given
+        let evaluator = evaluator_fake();
+        let inputs = [
+            (pos!(x 7:2; o 20:2), Dice::new(4, 2)),
+            (pos!(x 13:2, 6:3; o 12:2, 19:3), Dice::new(6, 1)),
+            (pos!(x 24:1, 8:2; o 1:1, 17:2), Dice::new(3, 3)),
+        ];
+        // When
+        let batch = evaluator.best_moves_batch(&inputs);
+        // Then
+        let one_at_a_time: Vec<Position> = inputs
+            .iter()
+            .map(|(pos, dice)| evaluator.best_position_by_equity(pos, dice))
+            .collect();
+        assert_eq!(batch, one_at_a_time);
+    }
+
+    #[test]
+    fn move_clusters_of_equal_and_far_away_moves() {
+        // Given
+        let pos = pos!(x 8:1, 6:1; o 20:1);
+        let dice = Dice::new(3, 1);
+        // The four legal moves are 8/5 6/5, 8/7 6/3, 8/4 and 6/2.
+        let best = pos!(x 5:2; o 20:1);
+        let close = pos!(x 7:1, 3:1; o 20:1);
+        let closest = pos!(x 6:1, 4:1; o 20:1);
+        let far = pos!(x 8:1, 2:1; o 20:1);
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut evaluator = EvaluatorFake::with_default([0.9, 0.0, 0.0, 0.1, 0.0, 0.0].into());
+        evaluator.insert(best.sides_switched(), [0.3, 0.0, 0.0, 0.7, 0.0, 0.0].into());
+        evaluator.insert(
+            close.sides_switched(),
+            [0.31, 0.0, 0.0, 0.69, 0.0, 0.0].into(),
+        );
+        evaluator.insert(
+            closest.sides_switched(),
+            [0.305, 0.0, 0.0, 0.695, 0.0, 0.0].into(),
+        );
+        // When
+        let clusters = evaluator.move_clusters(&pos, &dice, 0.05);
+        // Then
+        assert_eq!(clusters, vec![vec![best, closest, close], vec![far]]);
+    }
+
+    #[test]
+    fn best_completion_with_remaining_die() {
+        // Given
+        // The 5 of a 5-2 has been played already, either 7/5 or 5/3 is left with the 2.
+        let pos = pos!(x 7:1, 5:1; o 20:1);
+        let point_made = pos!(x 5:2; o 20:1).sides_switched();
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut evaluator = EvaluatorFake::with_default([0.6, 0.0, 0.0, 0.4, 0.0, 0.0].into());
+        evaluator.insert(point_made, [0.3, 0.0, 0.0, 0.7, 0.0, 0.0].into());
+        // When
+        let completed = evaluator.best_completion(&pos, 2);
+        // Then
+        assert_eq!(completed, point_made);
+        // Nothing can be moved with a 6, the checkers on 7 and 5 can't bear off.
+        assert_eq!(
+            evaluator.best_completion(&pos!(x 7:1, 5:1; o 1:2), 6),
+            pos!(x 7:1, 5:1; o 1:2).sides_switched()
+        );
+    }
+
+    #[test]
+    fn find_positions_with_equity_in_band() {
+        // Given
+        let pos = pos!(x 8:1, 6:1; o 20:1);
+        let dice = Dice::new(3, 1);
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut evaluator = EvaluatorFake::with_default([0.9, 0.0, 0.0, 0.1, 0.0, 0.0].into());
+        evaluator.insert(
+            pos!(x 5:2; o 20:1).sides_switched(),
+            [0.2, 0.0, 0.0, 0.8, 0.0, 0.0].into(),
+        );
+        evaluator.insert(
+            pos!(x 7:1, 3:1; o 20:1).sides_switched(),
+            [0.4, 0.0, 0.0, 0.6, 0.0, 0.0].into(),
+        );
+        // When
+        let unique = evaluator.find_positions_with_equity(&pos, &dice, 0.0, 0.5);
+        let all_but_best = evaluator.find_positions_with_equity(&pos, &dice, -0.9, 0.5);
+        // Then
+        assert_eq!(unique, vec![pos!(x 7:1, 3:1; o 20:1)]);
+        assert_eq!(all_but_best.len(), 3);
+        for position in all_but_best {
+            let equity = evaluator
+                .eval(&position.sides_switched())
+                .switch_sides()
+                .equity();
+            assert!((-0.9..=0.5).contains(&equity));
+        }
+    }
+
+    #[test]
+    fn market_losers_volatile_and_quiet_race() {
+        // Given
+        let evaluator = SingleCheckerRace(TrivialEndgame::new());
+        // Both players need two or three rolls, so a good roll for `x` and a bad one for `o` decide the race.
+        let volatile = pos!(x 10:1; o 15:1);
+        // `o` mostly bears off with the next roll, `x` rarely gets far enough ahead.
+        let quiet = pos!(x 6:1; o 20:1);
+        // When
+        let volatile_losers = evaluator.market_losers(&volatile);
+        let quiet_losers = evaluator.market_losers(&quiet);
+        // Then
+        assert!(volatile_losers > 0.5);
+        assert!(quiet_losers < 0.05);
+    }
+
+    #[test]
+    fn roll_luck_of_best_and_worst_roll() {
+        // Given
+        let evaluator = SingleCheckerRace(TrivialEndgame::new());
+        let pos = pos!(x 8:1; o 15:1);
+        // When
+        let best = evaluator.roll_luck(&pos, &Dice::new(6, 6));
+        let worst = evaluator.roll_luck(&pos, &Dice::new(2, 1));
+        // Then
+        assert!(best > 0.0);
+        assert!(worst < 0.0);
+    }
+
+    #[test]
+    fn next_roll_volatility_volatile_and_stable_race() {
+        // Given
+        let evaluator = SingleCheckerRace(TrivialEndgame::new());
+        // `o` bears off with the next roll, so this roll of `x` decides the race.
+        let volatile = pos!(x 8:1; o 20:1);
+        // `x` bears off with any roll.
+        let stable = pos!(x 2:1; o 20:1);
+        // When
+        let volatile_volatility = evaluator.next_roll_volatility(&volatile);
+        let stable_volatility = evaluator.next_roll_volatility(&stable);
+        // Then
+        assert!(volatile_volatility > 0.5);
+        assert!(stable_volatility < 0.001);
+    }
+
+    #[test]
+    fn opening_equities_of_best_and_worst_roll() {
+        // Given
+        let mut evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        // 31 played 8/5 6/5 makes the five point. The opponent is on roll after the move.
+        let five_point = pos!(x 24:2, 13:5, 8:2, 6:4, 5:2; o 19:5, 17:3, 12:5, 1:2);
+        evaluator.insert(
+            five_point.sides_switched(),
+            [0.4, 0.0, 0.0, 0.6, 0.0, 0.0].into(),
+        );
+        // When
+        let openings = evaluator.opening_equities();
+        // Then
+        assert_eq!(openings.len(), 15);
+        let (best_dice, best) = openings
+            .iter()
+            .max_by(|(_, a), (_, b)| a.cmp_by_equity(b))
+            .unwrap();
+        assert_eq!(*best_dice, Dice::new(3, 1));
+        assert!((best.equity() - 0.2).abs() < 0.0001);
+        let (_, worst) = openings
+            .iter()
+            .min_by(|(_, a), (_, b)| a.cmp_by_equity(b))
+            .unwrap();
+        assert!(best.equity() > worst.equity());
+    }
+
+    #[test]
+    fn pip_swing_in_race_is_biggest_pip_reduction() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        let pos = pos!(x 10:2, 8:1; o 15:3);
+        let dice = Dice::new(5, 3);
+        // When
+        let swing = evaluator.pip_swing(&pos, &dice);
+        // Then
+        let (x_before, _) = pos.pip_counts();
+        let biggest_reduction = pos
+            .all_positions_after_moving(&dice)
+            .iter()
+            .map(|after| x_before as i32 - after.pip_counts().1 as i32)
+            .max()
+            .unwrap();
+        assert_eq!(swing, 8);
+        assert_eq!(swing, biggest_reduction);
+    }
+
+    #[test]
+    fn eval_on_roll_and_not_on_roll_are_flips() {
+        // Given
+        let pos = pos!(x 6:1; o 19:8);
+        let mut evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        evaluator.insert(pos, [0.7, 0.1, 0.0, 0.2, 0.0, 0.0].into());
+        evaluator.insert(pos.sides_switched(), [0.6, 0.0, 0.0, 0.3, 0.1, 0.0].into());
+        // When
+        let on_roll = evaluator.eval_on_roll(&pos);
+        let not_on_roll = evaluator.eval_not_on_roll(&pos);
+        // Then
+        assert_eq!(on_roll, evaluator.eval(&pos));
+        assert_eq!(not_on_roll, [0.3, 0.1, 0.0, 0.6, 0.0, 0.0].into());
+        assert_eq!(
+            evaluator.eval_not_on_roll(&pos.sides_switched()),
+            on_roll.switch_sides()
+        );
+    }
+
+    #[test]
+    fn eval_both_sides_symmetric_position() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.4, 0.1, 0.0, 0.3, 0.15, 0.05].into());
+        let pos = pos!(x 24:2, 6:5; o 1:2, 19:5);
+        assert_eq!(pos, pos.sides_switched());
+        // When
+        let (x_on_roll, o_on_roll) = evaluator.eval_both_sides(&pos);
+        // Then
+        assert_eq!(o_on_roll, x_on_roll.switch_sides());
+        assert_eq!(x_on_roll.equity(), -o_on_roll.equity());
+    }
+
+    #[test]
+    fn eval_both_sides_is_from_perspective_of_x() {
+        // Given
+        let pos = pos!(x 6:1; o 19:8);
+        let mut evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        evaluator.insert(pos, [1.0, 0.0, 0.0, 0.0, 0.0, 0.0].into());
+        evaluator.insert(pos.sides_switched(), [0.9, 0.0, 0.0, 0.1, 0.0, 0.0].into());
+        // When
+        let (x_on_roll, o_on_roll) = evaluator.eval_both_sides(&pos);
+        // Then
+        assert_eq!(x_on_roll.win(), 1.0);
+        assert!((o_on_roll.win() - 0.1).abs() < 1e-6);
+    }
+
     #[test]
     fn best_position_by_equity() {
         // Given
@@ -287,6 +1032,48 @@ mod evaluator_trait_tests {
         assert_eq!(worst_probability.switch_sides(), evaluator.eval(&worst_pos));
     }
 
+    #[test]
+    fn positions_and_probabilities_by_equity_sorts_nan_last() {
+        // Given
+        let given_pos = pos!(x 7:2; o 20:2);
+        let mut evaluator = evaluator_fake();
+        let broken = pos!(x 7:1, 1:1; o 20:2).sides_switched();
+        evaluator.insert(broken, [f32::NAN, 0.0, 0.0, 1.0, 0.0, 0.0].into());
+        // When
+        let values = evaluator.positions_and_probabilities_by_equity(&given_pos, &Dice::new(4, 2));
+        // Then
+        let (last_pos, last_probability) = values.last().unwrap();
+        assert_eq!(last_pos.sides_switched(), broken);
+        assert!(last_probability.equity().is_nan());
+        assert_ne!(
+            evaluator.best_position_by_equity(&given_pos, &Dice::new(4, 2)),
+            broken
+        );
+    }
+
+    #[test]
+    fn reply_equities_show_single_dangerous_roll() {
+        // Given
+        // Only with 66 the opponent can bear off the checker on the 24 point before the one on the ace point.
+        let pos_after_move = pos!(x 24:1, 1:1; o 23:15);
+        let mut evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        evaluator.insert(
+            pos!(x 1:1; o 23:15).sides_switched(),
+            [0.1, 0.0, 0.0, 0.9, 0.0, 0.0].into(),
+        );
+        // When
+        let reply_equities = evaluator.reply_equities(&pos_after_move);
+        // Then
+        assert_eq!(reply_equities.len(), 21);
+        for (dice, equity) in reply_equities {
+            if dice == Dice::new(6, 6) {
+                assert!((equity - 0.8).abs() < 0.0001);
+            } else {
+                assert_eq!(equity, 0.0);
+            }
+        }
+    }
+
     #[test]
     fn eval_batch_empty() {
         // Given
@@ -313,6 +1100,17 @@ mod evaluator_trait_tests {
         assert_eq!(values[1].1, evaluator.eval(&pos_2));
         assert_ne!(evaluator.eval(&pos_1), evaluator.eval(&pos_2));
     }
+
+    #[test]
+    fn eval_slice_is_eval_batch() {
+        // Given
+        let positions = [pos!(x 7:2; o 20:2), position_with_lowest_equity()];
+        let evaluator = evaluator_fake();
+        // When
+        let values = evaluator.eval_slice(&positions);
+        // Then
+        assert_eq!(values, evaluator.eval_batch(positions.to_vec()));
+    }
 }
 
 #[cfg(test)]
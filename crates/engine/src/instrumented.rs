@@ -0,0 +1,117 @@
+use crate::evaluator::{Evaluator, EvaluatorInfo};
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Counts how many positions the wrapped evaluator evaluates, for example to see the real cost
+/// of a search configuration.
+///
+/// Positions evaluated with `eval_batch` or `eval_slice` are counted one by one, so a batch of all
+/// legal moves counts as many evaluations as there are moves. The counter is atomic, so the
+/// evaluator can also be shared between threads, for example in a rollout.
+pub struct InstrumentedEvaluator<T: Evaluator> {
+    evaluator: T,
+    count: AtomicUsize,
+}
+
+impl<T: Evaluator> InstrumentedEvaluator<T> {
+    pub fn new(evaluator: T) -> Self {
+        Self {
+            evaluator,
+            count: AtomicUsize::new(0),
+        }
+    }
+
+    /// Number of positions evaluated since creation or the last [InstrumentedEvaluator::reset].
+    pub fn eval_count(&self) -> usize {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    pub fn reset(&self) {
+        self.count.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<T: Evaluator> Evaluator for InstrumentedEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.evaluator.eval(pos)
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.count.fetch_add(positions.len(), Ordering::Relaxed);
+        self.evaluator.eval_batch(positions)
+    }
+
+    fn eval_slice(&self, positions: &[Position]) -> Vec<(Position, Probabilities)> {
+        self.count.fetch_add(positions.len(), Ordering::Relaxed);
+        self.evaluator.eval_slice(positions)
+    }
+
+    fn info(&self) -> EvaluatorInfo {
+        self.evaluator.info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::Dice;
+    use crate::evaluator::{Evaluator, EvaluatorFake};
+    use crate::instrumented::InstrumentedEvaluator;
+    use crate::multiply::MultiPlyEvaluator;
+    use crate::pos;
+
+    fn instrumented_fake() -> InstrumentedEvaluator<EvaluatorFake> {
+        InstrumentedEvaluator::new(EvaluatorFake::with_default(
+            [0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into(),
+        ))
+    }
+
+    #[test]
+    fn best_position_evaluates_each_legal_move_once() {
+        // Given
+        let evaluator = instrumented_fake();
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        let legal_moves = pos.all_positions_after_moving(&dice).len();
+        // When
+        let _ = evaluator.best_position_by_equity(&pos, &dice);
+        // Then
+        assert!(legal_moves > 1);
+        assert_eq!(evaluator.eval_count(), legal_moves);
+    }
+
+    #[test]
+    fn forced_and_winning_moves_are_not_evaluated() {
+        // Given
+        let evaluator = instrumented_fake();
+        // Double ones bear off both checkers, so there is only a single legal move.
+        let forced = pos!(x 2:1, 1:1; o 20:2);
+        // Bearing off both checkers wins, although there are other legal moves.
+        let winning = pos!(x 4:1, 2:1; o 20:2);
+        // When
+        let _ = evaluator.best_position_by_equity(&forced, &Dice::new(1, 1));
+        let _ = evaluator.best_position_by_equity(&winning, &Dice::new(4, 2));
+        // Then
+        assert_eq!(evaluator.eval_count(), 0);
+        evaluator.eval(&forced);
+        assert_eq!(evaluator.eval_count(), 1);
+        evaluator.reset();
+        assert_eq!(evaluator.eval_count(), 0);
+    }
+
+    #[test]
+    fn info_of_wrapped_evaluator_is_forwarded() {
+        // Given
+        let inner = MultiPlyEvaluator {
+            evaluator: EvaluatorFake::with_no_default(),
+        };
+        let expected = inner.info();
+        // When
+        let info = InstrumentedEvaluator::new(inner).info();
+        // Then
+        assert_eq!(info, expected);
+        assert_eq!(info.approx_ply, 1);
+        assert!(info.name.ends_with("EvaluatorFake"));
+    }
+}
@@ -0,0 +1,85 @@
+use crate::dice::{ALL_21, Dice};
+use crate::evaluator::PartialEvaluator;
+use crate::position::{Player, Position};
+use crate::probabilities::Probabilities;
+
+/// With five checkers or more, not even 6-6 can bear off all of them.
+const MAX_CHECKERS: u8 = 4;
+
+/// Exact evaluation of the last roll of a game: the opponent `o` bears off all checkers with any
+/// roll, so `x` has to finish with this roll or loses.
+///
+/// All 36 rolls of `x` are enumerated, `o` only rolls if `x` misses. As both players have already
+/// borne off at least one checker, there are no gammons.
+#[derive(Default)]
+pub struct LastRoll;
+
+impl PartialEvaluator for LastRoll {
+    /// Returns `None` unless both players have borne in and `o` certainly finishes with the next roll.
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+        let (x_off, o_off) = pos.checkers_off();
+        // At most `MAX_CHECKERS` left, but at least one off so that nobody can lose a gammon.
+        let min_off = pos.checkers_total().saturating_sub(MAX_CHECKERS).max(1);
+        if pos.is_game_over()
+            || x_off < min_off
+            || o_off < min_off
+            || pos.distance_to_bearoff(Player::X) > 0
+            || pos.distance_to_bearoff(Player::O) > 0
+        {
+            return None;
+        }
+        let opponent = pos.sides_switched();
+        if !ALL_21.iter().all(|(dice, _)| finishes(&opponent, dice)) {
+            return None;
+        }
+        let wins: usize = ALL_21
+            .iter()
+            .filter(|(dice, _)| finishes(pos, dice))
+            .map(|(_, amount)| amount)
+            .sum();
+        let win = wins as f32 / 36.0;
+        Some(Probabilities {
+            win_normal: win,
+            lose_normal: 1.0 - win,
+            ..Default::default()
+        })
+    }
+}
+
+/// `true` if `x` can bear off all checkers with `dice`.
+fn finishes(pos: &Position, dice: &Dice) -> bool {
+    pos.all_positions_after_moving(dice)
+        .iter()
+        .any(|position| position.is_game_over())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::PartialEvaluator;
+    use crate::last_roll::LastRoll;
+    use crate::pos;
+
+    #[test]
+    fn both_bear_off_this_roll() {
+        let probabilities = LastRoll.try_eval(&pos!(x 1:2; o 24:2)).unwrap();
+        assert_eq!(probabilities.win_normal, 1.0);
+        assert_eq!(probabilities.lose_normal, 0.0);
+        assert_eq!(probabilities.win_gammon, 0.0);
+    }
+
+    #[test]
+    fn player_on_roll_sometimes_misses() {
+        // Only 6-5 and the doubles 3-3 to 6-6 bear off both checkers.
+        let probabilities = LastRoll.try_eval(&pos!(x 6:1, 5:1; o 24:1)).unwrap();
+        assert_eq!(probabilities.win_normal, 6.0 / 36.0);
+        assert_eq!(probabilities.lose_normal, 30.0 / 36.0);
+    }
+
+    #[test]
+    fn none_if_opponent_might_miss() {
+        assert_eq!(LastRoll.try_eval(&pos!(x 1:1; o 19:2)), None);
+        assert_eq!(LastRoll.try_eval(&pos!(x 1:1; o 24:5)), None);
+        assert_eq!(LastRoll.try_eval(&pos!(x 7:1; o 24:1)), None);
+        assert_eq!(LastRoll.try_eval(&pos!(x 1:1; o 10:1)), None);
+    }
+}
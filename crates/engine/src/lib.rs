@@ -1,9 +1,15 @@
+pub mod bearoff;
 pub mod composite;
 pub mod dice;
 pub mod dice_gen;
 pub mod evaluator;
 pub mod inputs;
+pub mod instrumented;
+pub mod last_roll;
+pub mod logging;
 pub mod multiply;
 pub mod onnx;
 pub mod position;
 pub mod probabilities;
+pub mod trivial_endgame;
+pub mod win_only;
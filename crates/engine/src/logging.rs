@@ -0,0 +1,116 @@
+use crate::evaluator::{Evaluator, EvaluatorInfo};
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+use std::io::Write;
+use std::sync::Mutex;
+
+/// Writes every evaluation of the wrapped evaluator to `writer`, for example to debug the behavior
+/// of a neural net in production.
+///
+/// Each evaluated position is one line with the GnuBG position ID, the six probabilities and the
+/// cubeless equity, separated by semicolons like in [Probabilities::csv_header]. The writer is
+/// behind a mutex, so the evaluator can also be shared between threads, for example in a rollout.
+/// Errors while writing are ignored, logging must not stop the evaluation.
+pub struct LoggingEvaluator<T: Evaluator, W: Write> {
+    evaluator: T,
+    writer: Mutex<W>,
+}
+
+impl<T: Evaluator, W: Write> LoggingEvaluator<T, W> {
+    pub fn new(evaluator: T, writer: W) -> Self {
+        Self {
+            evaluator,
+            writer: Mutex::new(writer),
+        }
+    }
+
+    /// Returns the writer, for example to read the lines written to a `Vec<u8>`.
+    pub fn into_writer(self) -> W {
+        self.writer
+            .into_inner()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn log(&self, evaluations: &[(Position, Probabilities)]) {
+        if let Ok(mut writer) = self.writer.lock() {
+            for (position, probabilities) in evaluations {
+                let _ = writeln!(
+                    writer,
+                    "{};{};{}",
+                    position.position_id(),
+                    probabilities,
+                    probabilities.equity()
+                );
+            }
+        }
+    }
+}
+
+impl<T: Evaluator, W: Write> Evaluator for LoggingEvaluator<T, W> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        let probabilities = self.evaluator.eval(pos);
+        self.log(&[(*pos, probabilities.clone())]);
+        probabilities
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        let evaluations = self.evaluator.eval_batch(positions);
+        self.log(&evaluations);
+        evaluations
+    }
+
+    fn eval_slice(&self, positions: &[Position]) -> Vec<(Position, Probabilities)> {
+        let evaluations = self.evaluator.eval_slice(positions);
+        self.log(&evaluations);
+        evaluations
+    }
+
+    fn info(&self) -> EvaluatorInfo {
+        self.evaluator.info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::evaluator::{Evaluator, EvaluatorFake};
+    use crate::logging::LoggingEvaluator;
+    use crate::pos;
+    use crate::position::{Position, STARTING};
+
+    #[test]
+    fn each_evaluation_is_one_line() {
+        // Given
+        let fake = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        let evaluator = LoggingEvaluator::new(fake, Vec::new());
+        let positions = [STARTING, pos!(x 7:2; o 20:2), pos!(x 6:1; o 19:1)];
+        // When
+        for pos in &positions {
+            evaluator.eval(pos);
+        }
+        // Then
+        let output = String::from_utf8(evaluator.into_writer()).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 3);
+        for (line, pos) in lines.iter().zip(positions) {
+            let fields: Vec<&str> = line.split(';').collect();
+            assert_eq!(fields.len(), 8);
+            assert_eq!(Position::from_id(fields[0]), pos);
+            let values: Vec<f32> = fields[1..].iter().map(|f| f.parse().unwrap()).collect();
+            assert_eq!(values[0], 0.38);
+            assert!((values[6] - 0.46).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn batch_evaluations_are_logged() {
+        // Given
+        let fake = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        let evaluator = LoggingEvaluator::new(fake, Vec::new());
+        // When
+        let evaluations = evaluator.eval_batch(vec![STARTING, pos!(x 7:2; o 20:2)]);
+        // Then
+        assert_eq!(evaluations.len(), 2);
+        let output = String::from_utf8(evaluator.into_writer()).unwrap();
+        assert_eq!(output.lines().count(), 2);
+    }
+}
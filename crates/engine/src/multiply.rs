@@ -1,5 +1,6 @@
 use crate::dice::ALL_21;
-use crate::evaluator::Evaluator;
+use crate::evaluator::{Evaluator, EvaluatorInfo};
+use crate::position::GameState::GameOver;
 use crate::position::Position;
 use crate::probabilities::Probabilities;
 
@@ -14,6 +15,40 @@ pub struct MultiPlyEvaluator<T: Evaluator> {
     pub evaluator: T,
 }
 
+impl<T: Evaluator> MultiPlyEvaluator<T> {
+    /// Evaluations of `pos` looking `0`, `1`, ..., `max` plies ahead, for example to see how the
+    /// evaluation converges with depth.
+    ///
+    /// At each ply the move for each roll is chosen by the wrapped evaluator, so only the
+    /// evaluation of the position at the deepest ply uses it directly. The entry at index `1` is
+    /// the same as [Evaluator::eval] of this struct.
+    pub fn eval_all_plies(&self, pos: &Position, max: u8) -> Vec<Probabilities> {
+        (0..=max)
+            .map(|plies| self.eval_at_ply(pos, plies))
+            .collect()
+    }
+
+    fn eval_at_ply(&self, pos: &Position, plies: u8) -> Probabilities {
+        if plies == 0 {
+            return self.evaluator.eval(pos);
+        }
+        if let GameOver(result) = pos.game_state() {
+            return Probabilities::from(result);
+        }
+        let sum = ALL_21
+            .iter()
+            .map(|(dice, number)| {
+                // The best position is from the perspective of the opponent, who is on roll.
+                let best = self.evaluator.best_position_by_equity(pos, dice);
+                self.eval_at_ply(&best, plies - 1).switch_sides() * *number as f32
+            })
+            .fold(Probabilities::default(), |sum, probabilities| {
+                sum + probabilities
+            });
+        sum * (1.0 / 36.0)
+    }
+}
+
 impl<T: Evaluator> Evaluator for MultiPlyEvaluator<T> {
     fn eval(&self, position: &Position) -> Probabilities {
         let mut win_normal = 0f32;
@@ -51,15 +86,41 @@ impl<T: Evaluator> Evaluator for MultiPlyEvaluator<T> {
             lose_bg: lose_bg / 36f32,
         }
     }
+
+    /// Looks one ply further ahead than the wrapped evaluator.
+    fn info(&self) -> EvaluatorInfo {
+        let info = self.evaluator.info();
+        EvaluatorInfo {
+            approx_ply: info.approx_ply.saturating_add(1),
+            ..info
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::composite::CompositeEvaluator;
-    use crate::evaluator::Evaluator;
+    use crate::evaluator::{Evaluator, EvaluatorFake};
     use crate::multiply::MultiPlyEvaluator;
     use crate::pos;
 
+    #[test]
+    fn eval_all_plies_starts_with_inner_evaluation() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.6, 0.1, 0.0, 0.3, 0.0, 0.0].into());
+        let pos = pos!(x 8:2, 6:2; o 17:2, 19:2);
+        let inner = evaluator.eval(&pos);
+        let multi = MultiPlyEvaluator { evaluator };
+        // When
+        let all_plies = multi.eval_all_plies(&pos, 2);
+        // Then
+        assert_eq!(all_plies.len(), 3);
+        assert_eq!(all_plies[0], inner);
+        assert!((all_plies[1].equity() - multi.eval(&pos).equity()).abs() < 0.0001);
+        // The fake evaluates all positions the same, so each ply flips the perspective.
+        assert!((all_plies[2].equity() - inner.equity()).abs() < 0.0001);
+    }
+
     #[test]
     fn equity_is_average_of_1ply_ahead_equities() {
         let evaluator = CompositeEvaluator::default_tests();
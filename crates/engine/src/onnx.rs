@@ -3,7 +3,7 @@ use std::fs::File;
 use tract_onnx::prelude::*;
 use tract_onnx::tract_hir::shapefactoid;
 
-use crate::evaluator::BatchEvaluator;
+use crate::evaluator::{BatchEvaluator, EvaluatorInfo};
 use crate::inputs::{ContactInputsGen, InputsGen, RaceInputsGen};
 use crate::position::Position;
 use crate::probabilities::Probabilities;
@@ -48,18 +48,30 @@ impl<T: InputsGen> BatchEvaluator for OnnxEvaluator<T> {
         // Extract all the probabilities from the result:
         let array_view = result[0].to_array_view::<f32>().unwrap();
         let probabilities_in_shape = array_view.to_shape((positions.len(), 6)).unwrap();
-        let probabilities_iter = probabilities_in_shape.outer_iter().map(|x| Probabilities {
-            win_normal: x[0],
-            win_gammon: x[1],
-            win_bg: x[2],
-            lose_normal: x[3],
-            lose_gammon: x[4],
-            lose_bg: x[5],
+        // The outputs of the nets are numerically noisy, so they are cleaned up right here.
+        let probabilities_iter = probabilities_in_shape.outer_iter().map(|x| {
+            Probabilities {
+                win_normal: x[0],
+                win_gammon: x[1],
+                win_bg: x[2],
+                lose_normal: x[3],
+                lose_gammon: x[4],
+                lose_bg: x[5],
+            }
+            .clamp01_and_renormalize()
         });
         let positions_and_probabilities: Vec<(Position, Probabilities)> =
             positions.into_iter().zip(probabilities_iter).collect();
         positions_and_probabilities
     }
+
+    fn batch_info(&self) -> EvaluatorInfo {
+        EvaluatorInfo {
+            name: "Neural net",
+            approx_ply: 0,
+            is_rollout: false,
+        }
+    }
 }
 
 impl<T: InputsGen> OnnxEvaluator<T> {
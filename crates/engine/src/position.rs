@@ -1,6 +1,7 @@
 mod conversion;
 mod double_moves;
 mod mixed_moves;
+pub mod presets;
 
 use crate::dice::Dice;
 use crate::position::GameResult::*;
@@ -11,7 +12,7 @@ use std::fmt;
 use std::fmt::Formatter;
 use std::fmt::Write;
 
-const NUM_OF_CHECKERS: u8 = 15;
+pub(crate) const NUM_OF_CHECKERS: u8 = 15;
 pub const X_BAR: usize = 25;
 pub const O_BAR: usize = 0;
 
@@ -27,19 +28,40 @@ pub const STARTING: Position = Position {
     ],
     x_off: 0,
     o_off: 0,
+    checkers: NUM_OF_CHECKERS,
 };
 
+/// Starting position of Hypergammon, a variant with 3 checkers per player on the 24, 23 and 22 point.
+pub const HYPERGAMMON_STARTING: Position = Position {
+    pips: [
+        0, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 0,
+    ],
+    x_off: 0,
+    o_off: 0,
+    checkers: 3,
+};
+
+/// Result of a finished game from the perspective of player `x` of the [Position].
+///
+/// Gammons and backgammons are cubeless: a gammon is worth two points and a backgammon three.
 #[derive(Clone, Debug, PartialEq)]
 pub enum GameResult {
+    /// `x` has borne off all checkers, `o` has borne off at least one checker.
     WinNormal,
+    /// `x` has borne off all checkers, `o` none, but `o` has no checkers in `x`'s home board or on the bar.
     WinGammon,
+    /// `x` has borne off all checkers, `o` none and still has checkers in `x`'s home board or on the bar.
     WinBg,
+    /// `o` has borne off all checkers, `x` has borne off at least one checker.
     LoseNormal,
+    /// `o` has borne off all checkers, `x` none, but `x` has no checkers in `o`'s home board or on the bar.
     LoseGammon,
+    /// `o` has borne off all checkers, `x` none and still has checkers in `o`'s home board or on the bar.
     LoseBg,
 }
 
 impl GameResult {
+    /// The same result from the perspective of the other player: a win becomes a loss and vice versa.
     pub fn reverse(&self) -> Self {
         match self {
             WinNormal => LoseNormal,
@@ -52,12 +74,83 @@ impl GameResult {
     }
 }
 
+impl From<GameResult> for i8 {
+    /// Points won by `x`, negative for a loss: `WinBg` is `3`, `LoseNormal` is `-1`.
+    ///
+    /// Compact for storing results, for example as labels of training data.
+    /// [GameResult::reverse] is just the negation.
+    fn from(value: GameResult) -> Self {
+        match value {
+            WinNormal => 1,
+            WinGammon => 2,
+            WinBg => 3,
+            LoseNormal => -1,
+            LoseGammon => -2,
+            LoseBg => -3,
+        }
+    }
+}
+
+impl TryFrom<i8> for GameResult {
+    type Error = &'static str;
+
+    /// The reverse of `From<GameResult> for i8`.
+    fn try_from(value: i8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(WinNormal),
+            2 => Ok(WinGammon),
+            3 => Ok(WinBg),
+            -1 => Ok(LoseNormal),
+            -2 => Ok(LoseGammon),
+            -3 => Ok(LoseBg),
+            _ => Err("A game result must be between -3 and 3, but not 0."),
+        }
+    }
+}
+
+/// Whether a [Position] is still being played, returned by [Position::game_state].
+///
+/// Like the [GameResult], this is from the perspective of player `x`. Keep in mind that
+/// [Position::all_positions_after_moving] returns positions which have already switched sides,
+/// so if the player who just moved has won, the result there is a loss.
+///
+/// ```
+/// use engine::dice::Dice;
+/// use engine::pos;
+/// use engine::position::{GameResult, GameState};
+///
+/// // `x` bears off the last checker, `o` has already borne off one checker.
+/// let position = pos!(x 1:1; o 24:14);
+/// let after_moving = position.all_positions_after_moving(&Dice::new(2, 1));
+///
+/// match after_moving[0].game_state() {
+///     GameState::Ongoing => unreachable!("x has borne off all checkers"),
+///     // `o` is on turn now, so we reverse the result to get it for the player who moved.
+///     GameState::GameOver(result) => assert_eq!(result.reverse(), GameResult::WinNormal),
+/// }
+/// ```
 #[derive(Debug, PartialEq)]
 pub enum GameState {
+    /// Neither player has borne off all checkers yet.
     Ongoing,
+    /// One of the players has borne off all checkers.
     GameOver(GameResult),
 }
 
+/// One of the two players of a [Position]. `X` is the player on turn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Player {
+    X,
+    O,
+}
+
+/// Whether the difference of the pip counts of both players is even or odd.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Parity {
+    Even,
+    Odd,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum OngoingPhase {
     Contact,
@@ -72,6 +165,11 @@ pub enum GamePhase {
 
 /// A single position in backgammon without match information.
 /// We assume two players "x" and "o".
+///
+/// By convention `x` is always the player on roll: evaluators return probabilities for `x` and
+/// move generation moves `x`'s checkers. After a move, the positions are returned from the
+/// perspective of the opponent, who is on roll next. So "x" and "o" don't name fixed players of a
+/// game, they swap after each half move.
 #[derive(Copy, Clone, PartialEq, Eq, Hash)]
 pub struct Position {
     // Array positions 25 and 0 are the bar.
@@ -81,6 +179,9 @@ pub struct Position {
     pub(crate) pips: [i8; 26],
     x_off: u8,
     o_off: u8,
+    // Number of checkers of each player: 15, or 3 in Hypergammon. A player who has borne off
+    // that many checkers has won.
+    checkers: u8,
 }
 
 impl Position {
@@ -100,29 +201,75 @@ impl Position {
         self.pips[pip]
     }
 
+    /// Checkers on a point, the minimal read access to the board for custom evaluators or renderers.
+    ///
+    /// Positive values are checkers of `x`, negative values checkers of `o`. Points `1` to `24`
+    /// are numbered from the perspective of `x`, [X_BAR] (`25`) is the bar of `x` and [O_BAR] (`0`)
+    /// is the bar of `o`. Checkers which are borne off are returned by [Position::checkers_off].
+    /// Panics if `point` is bigger than `25`.
+    #[inline]
+    pub fn checkers_on(&self, point: usize) -> i8 {
+        self.pips[point]
+    }
+
+    /// Number of checkers borne off by `x` and `o`.
+    #[inline]
+    pub fn checkers_off(&self) -> (u8, u8) {
+        (self.x_off, self.o_off)
+    }
+
+    /// Number of checkers of each player: 15 in standard backgammon, 3 in Hypergammon.
+    #[inline]
+    pub fn checkers_total(&self) -> u8 {
+        self.checkers
+    }
+
     #[inline]
     pub fn has_lost(&self) -> bool {
-        self.o_off == NUM_OF_CHECKERS
+        self.o_off == self.checkers
+    }
+
+    /// Whether `player` has borne off at least one checker.
+    ///
+    /// A player who has lost without bearing off any checker has lost at least a gammon.
+    #[inline]
+    pub fn has_borne_off_any(&self, player: Player) -> bool {
+        match player {
+            Player::X => self.x_off > 0,
+            Player::O => self.o_off > 0,
+        }
+    }
+
+    /// Whether `player` has a checker on the bar or in the home board of the opponent.
+    ///
+    /// A player who has lost a gammon with such a checker has lost a backgammon.
+    #[inline]
+    pub fn has_checker_in_opponent_home_or_bar(&self, player: Player) -> bool {
+        match player {
+            Player::X => self.pips[19..(X_BAR + 1)].iter().any(|pip| pip > &0),
+            Player::O => self.pips[O_BAR..7].iter().any(|pip| pip < &0),
+        }
     }
 
+    /// Whether the game is over and if so, the result from the perspective of `x`.
     #[inline]
     pub fn game_state(&self) -> GameState {
         debug_assert!(
-            self.x_off < NUM_OF_CHECKERS || self.o_off < NUM_OF_CHECKERS,
+            self.x_off < self.checkers || self.o_off < self.checkers,
             "Not both sides can win at the same time"
         );
-        if self.x_off == NUM_OF_CHECKERS {
-            if self.o_off > 0 {
+        if self.x_off == self.checkers {
+            if self.has_borne_off_any(Player::O) {
                 GameOver(WinNormal)
-            } else if self.pips[O_BAR..7].iter().any(|pip| pip < &0) {
+            } else if self.has_checker_in_opponent_home_or_bar(Player::O) {
                 GameOver(WinBg)
             } else {
                 GameOver(WinGammon)
             }
-        } else if self.o_off == NUM_OF_CHECKERS {
-            if self.x_off > 0 {
+        } else if self.o_off == self.checkers {
+            if self.has_borne_off_any(Player::X) {
                 GameOver(LoseNormal)
-            } else if self.pips[19..(X_BAR + 1)].iter().any(|pip| pip > &0) {
+            } else if self.has_checker_in_opponent_home_or_bar(Player::X) {
                 GameOver(LoseBg)
             } else {
                 GameOver(LoseGammon)
@@ -132,6 +279,24 @@ impl Position {
         }
     }
 
+    /// `true` if one of the players has borne off all checkers, see [Position::game_state].
+    #[inline]
+    pub fn is_game_over(&self) -> bool {
+        self.x_off == self.checkers || self.o_off == self.checkers
+    }
+
+    /// The player who has borne off all checkers, `None` while the game is ongoing.
+    #[inline]
+    pub fn winner(&self) -> Option<Player> {
+        if self.x_off == self.checkers {
+            Some(Player::X)
+        } else if self.o_off == self.checkers {
+            Some(Player::O)
+        } else {
+            None
+        }
+    }
+
     /// Returns more info than `game_state` - not only whether the game is still ongoing, but also
     /// whether we are already in the race phase.
     ///
@@ -163,19 +328,38 @@ impl Position {
     }
 
     /// The return values have switched the sides of the players.
+    ///
+    /// The order of the returned positions is deterministic, it only depends on `self` and `dice`.
+    /// It's the order in which move generation finds the moves, iterating over the pips and dice.
+    /// No randomness or hashing is involved. This order is relied upon: evaluators pick the first
+    /// of several equally good positions, so a different order would change which move is played.
     pub fn all_positions_after_moving(&self, dice: &Dice) -> Vec<Position> {
-        debug_assert!(self.o_off < NUM_OF_CHECKERS && self.x_off < NUM_OF_CHECKERS);
-        let mut new_positions = match dice {
-            Dice::Double(die) => self.all_positions_after_double_move(*die),
-            Dice::Mixed(dice) => self.all_positions_after_mixed_move(dice),
+        let mut new_positions = Vec::with_capacity(MOVES_CAPACITY);
+        self.all_positions_after_moving_into(dice, &mut new_positions);
+        new_positions
+    }
+
+    /// Like [Position::all_positions_after_moving], but writes the positions into `buffer`.
+    ///
+    /// `buffer` is cleared first. Reusing the same buffer for many calls, for example during a
+    /// rollout, avoids allocating a new vector for each move.
+    pub fn all_positions_after_moving_into(&self, dice: &Dice, buffer: &mut Vec<Position>) {
+        debug_assert!(self.o_off < self.checkers && self.x_off < self.checkers);
+        buffer.clear();
+        match dice {
+            Dice::Double(die) => self.push_positions_after_double_move(*die, buffer),
+            Dice::Mixed(dice) => self.push_positions_after_mixed_move(dice, buffer),
         };
-        for position in new_positions.iter_mut() {
+        for position in buffer.iter_mut() {
             *position = position.sides_switched();
         }
-        debug_assert!(!new_positions.is_empty());
-        new_positions
+        debug_assert!(!buffer.is_empty());
     }
 
+    /// The same position from the perspective of `o`: `o`'s checkers become `x`'s and vice versa.
+    ///
+    /// This only relabels the players. Whether the result is the position of the player on roll
+    /// depends on the caller, see [Position::with_opponent_to_move] for the common case.
     #[inline]
     pub fn sides_switched(&self) -> Position {
         let mut pips = [0; 26];
@@ -186,8 +370,252 @@ impl Position {
             x_off: self.o_off,
             o_off: self.x_off,
             pips,
+            checkers: self.checkers,
         }
     }
+
+    /// The position after `x` has finished moving, ready for the opponent to roll.
+    ///
+    /// Since `x` is always the player on roll, this is the same board as seen by the opponent:
+    /// the result is identical to [Position::sides_switched]. Prefer this name when the turn passes.
+    #[inline]
+    pub fn with_opponent_to_move(&self) -> Position {
+        self.sides_switched()
+    }
+
+    /// Number of checkers of `x` and `o`, including checkers on the bar and borne off.
+    ///
+    /// In any legal position this is [Position::checkers_total] for both players: `(15, 15)` in
+    /// standard backgammon and `(3, 3)` for [HYPERGAMMON_STARTING].
+    pub fn count_checkers(&self) -> (u8, u8) {
+        let x_on_board: u32 = self.pips.iter().map(|&p| p.max(0) as u32).sum();
+        let o_on_board: u32 = self.pips.iter().map(|&p| (-p).max(0) as u32).sum();
+        let count = |on_board: u32, off: u8| u8::try_from(on_board + off as u32).unwrap_or(u8::MAX);
+        (count(x_on_board, self.x_off), count(o_on_board, self.o_off))
+    }
+
+    /// Checks that the position is legal: Each player has all checkers of the variant, usually 15,
+    /// on the board, on the bar or borne off. Checkers are only on the bar of their own player and
+    /// not both players are done.
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let (x_checkers, o_checkers) = self.count_checkers();
+        if x_checkers != self.checkers {
+            Err("Player x doesn't have all checkers of the variant.")
+        } else if o_checkers != self.checkers {
+            Err("Player o doesn't have all checkers of the variant.")
+        } else if self.pips[X_BAR].is_negative() {
+            Err("Index 25 is the bar for player x, number of checkers needs to be positive.")
+        } else if self.pips[O_BAR].is_positive() {
+            Err("Index 0 is the bar for player o, number of checkers needs to be negative.")
+        } else if self.x_off == self.checkers && self.o_off == self.checkers {
+            Err("Not both players can have borne off all checkers.")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Number of points with at least two checkers in the home boards of `x` and `o`.
+    ///
+    /// The home board of `x` are the pips 1 to 6, the home board of `o` are the pips 19 to 24.
+    pub fn home_board_points_made(&self) -> (u8, u8) {
+        let x_points = self.pips[1..7].iter().filter(|&&p| p >= 2).count();
+        let o_points = self.pips[19..25].iter().filter(|&&p| p <= -2).count();
+        (x_points as u8, o_points as u8)
+    }
+
+    /// Pip counts of `x` and `o`: the pips each player has to move to bear off all checkers.
+    ///
+    /// Checkers on the bar count 25 pips.
+    pub fn pip_counts(&self) -> (u32, u32) {
+        let x_pips = (1..=X_BAR)
+            .map(|i| i as u32 * self.pips[i].max(0) as u32)
+            .sum();
+        let o_pips = (O_BAR..X_BAR)
+            .map(|i| (X_BAR - i) as u32 * (-self.pips[i]).max(0) as u32)
+            .sum();
+        (x_pips, o_pips)
+    }
+
+    /// Pip count of a single player, see [Position::pip_counts].
+    pub fn pip_count_for(&self, player: Player) -> u32 {
+        let (x_pips, o_pips) = self.pip_counts();
+        match player {
+            Player::X => x_pips,
+            Player::O => o_pips,
+        }
+    }
+
+    /// Parity of the difference between the pip counts of `x` and `o`.
+    ///
+    /// Relevant for the timing of backgames: it decides which player is forced to break a point
+    /// first when both players are waiting.
+    pub fn pip_parity(&self) -> Parity {
+        let (x_pips, o_pips) = self.pip_counts();
+        if x_pips.abs_diff(o_pips).is_multiple_of(2) {
+            Parity::Even
+        } else {
+            Parity::Odd
+        }
+    }
+
+    /// Contribution of each point to the pip count of `player`: checkers times distance.
+    ///
+    /// Points are numbered from the perspective of `player`, point `p` is at index `p - 1`. The last
+    /// index is the bar, which counts as point 25. The sum is the pip count of [Position::pip_counts].
+    pub fn pip_breakdown(&self, player: Player) -> [u32; 25] {
+        let pos = match player {
+            Player::X => *self,
+            Player::O => self.sides_switched(),
+        };
+        let mut breakdown = [0; 25];
+        for point in 1..=X_BAR {
+            breakdown[point - 1] = point as u32 * pos.pips[point].max(0) as u32;
+        }
+        breakdown
+    }
+
+    /// Rough attack strength of `x` against the checkers of `o` in the home board of `x`.
+    ///
+    /// Each home board point made by `x` counts `1`, each checker of `o` on the bar `1.5` and each
+    /// blot of `o` in the home board of `x` `0.5`. A blitz with four home points, two checkers on the
+    /// bar and two blots scores `8`, the starting position `1`.
+    pub fn blitz_score(&self) -> f32 {
+        let (home_points, _) = self.home_board_points_made();
+        let on_bar = -self.pips[O_BAR] as f32;
+        let blots = self.pips[1..7].iter().filter(|&&p| p == -1).count() as f32;
+        home_points as f32 + 1.5 * on_bar + 0.5 * blots
+    }
+
+    /// Length of the longest run of consecutive points with at least two checkers of `player`.
+    ///
+    /// A 6-prime is a full block.
+    pub fn longest_prime(&self, player: Player) -> u8 {
+        let pos = match player {
+            Player::X => *self,
+            Player::O => self.sides_switched(),
+        };
+        let mut longest = 0;
+        let mut current = 0;
+        for &pip in &pos.pips[1..25] {
+            if pip >= 2 {
+                current += 1;
+                longest = longest.max(current);
+            } else {
+                current = 0;
+            }
+        }
+        longest
+    }
+
+    /// Pips the rearmost checker of `player` has to move to reach the home board.
+    ///
+    /// Once this is `0`, `player` can bear off. A checker on the bar counts as being on point 25,
+    /// so it's 19 pips away from the home board. Also `0` if `player` has no checkers left.
+    pub fn distance_to_bearoff(&self, player: Player) -> u8 {
+        let pos = match player {
+            Player::X => *self,
+            Player::O => self.sides_switched(),
+        };
+        match pos.pips.iter().rposition(|&p| p > 0) {
+            Some(rearmost) => rearmost.saturating_sub(6) as u8,
+            None => 0,
+        }
+    }
+
+    /// Number of checkers of `o` which still have to pass the rearmost checker of `x`, including
+    /// checkers of `o` on the bar.
+    ///
+    /// Zero means there is no contact anymore, the position is a pure race like in [Position::game_phase].
+    /// Few contact checkers mean the contact is about to be broken, which makes the position less
+    /// volatile for cube decisions.
+    pub fn contact_points(&self) -> u8 {
+        match self.pips.iter().rposition(|&p| p > 0) {
+            Some(rearmost) => self.pips[..rearmost]
+                .iter()
+                .filter(|&&p| p < 0)
+                .map(|&p| -p as u8)
+                .sum(),
+            None => 0,
+        }
+    }
+
+    /// Rough winning probability of `x`, who is on roll, in a race. `None` if there is still contact
+    /// or the game is over.
+    ///
+    /// Based on the Kleinman count: with the pip lead `D` (adding 4 pips for being on roll) and the
+    /// sum `S` of both pip counts, `x` wins with about `Φ((D + 4) / √(2 (S - 4)))`. The normal
+    /// distribution `Φ` is approximated with a logistic function. Much cheaper than an evaluation,
+    /// but only good enough for things like a status bar.
+    pub fn race_estimate(&self) -> Option<f32> {
+        if self.game_phase() != GamePhase::Ongoing(Race) {
+            return None;
+        }
+        let (x_pips, o_pips) = self.pip_counts();
+        let lead = o_pips as f32 - x_pips as f32 + 4.0;
+        let sum = (x_pips + o_pips) as f32;
+        let z = lead / (2.0 * (sum - 4.0).max(1.0)).sqrt();
+        Some(1.0 / (1.0 + (-1.702 * z).exp()))
+    }
+
+    /// Pips wasted in the bearoff by `x` and `o`: effective pip count minus raw pip count.
+    ///
+    /// The effective pip count is approximated with Walter Trice's rules of thumb:
+    /// - 2 pips for each checker on the ace point, 1 pip for each checker on the 2 and 3 point,
+    /// - 1 pip for each checker beyond the third on any point of the home board,
+    /// - 1 pip for each empty 4, 5 or 6 point below the highest occupied point.
+    ///
+    /// Only checkers in the home board are taken into account, so the numbers are meaningful
+    /// once a player has borne in.
+    pub fn bearoff_wastage(&self) -> (u32, u32) {
+        fn wastage(home: &[i8]) -> u32 {
+            // `home[0]` is the ace point, `home[5]` the six point.
+            let checkers = |i: usize| home[i].max(0) as u32;
+            let low_points = 2 * checkers(0) + checkers(1) + checkers(2);
+            let stacks: u32 = (0..6).map(|i| checkers(i).saturating_sub(3)).sum();
+            let gaps = match (0..6).rev().find(|&i| checkers(i) > 0) {
+                Some(highest) => (3..highest).filter(|&i| checkers(i) == 0).count() as u32,
+                None => 0,
+            };
+            low_points + stacks + gaps
+        }
+        let switched = self.sides_switched();
+        (wastage(&self.pips[1..7]), wastage(&switched.pips[1..7]))
+    }
+
+    /// The points on which the checkers of `x` differ between `self` and `other`.
+    ///
+    /// `other` is typically the position after a move of `x` and must still be from the
+    /// perspective of `x`, so not switched like the positions of [Position::all_positions_after_moving].
+    pub fn diff(&self, other: &Position) -> PositionDiff {
+        let mut diff = PositionDiff::default();
+        for pip in (1..=X_BAR).rev() {
+            let before = self.pips[pip].max(0) as u8;
+            let after = other.pips[pip].max(0) as u8;
+            if before > after {
+                diff.sources.push((pip, before - after));
+            } else if after > before {
+                diff.destinations.push((pip, after - before));
+            }
+            if pip < X_BAR && self.pips[pip] == -1 && other.pips[pip] >= 0 {
+                diff.hits.push(pip);
+            }
+        }
+        if other.x_off > self.x_off {
+            diff.destinations.push((0, other.x_off - self.x_off));
+        }
+        diff
+    }
+}
+
+/// Result of [Position::diff]: only the changed points, all sorted from bigger to smaller pips.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PositionDiff {
+    /// Points which have fewer checkers of `x` and by how many. The bar is `25`.
+    pub sources: Vec<(usize, u8)>,
+    /// Points which have more checkers of `x` and by how many. Borne off checkers are on `0`.
+    pub destinations: Vec<(usize, u8)>,
+    /// Points on which a blot of `o` was hit.
+    pub hits: Vec<usize>,
 }
 
 impl From<Position> for [i8; 26] {
@@ -204,14 +632,17 @@ impl TryFrom<[i8; 26]> for Position {
     /// Checkers already off the board are calculated based on the input array.
     /// Will return an error if the sum of checkers for `x` or `o` is bigger than 15.
     fn try_from(pips: [i8; 26]) -> Result<Self, Self::Error> {
-        let x_off: i8 =
-            (NUM_OF_CHECKERS as i8) - pips.iter().filter(|p| p.is_positive()).sum::<i8>();
-        let o_off: i8 =
-            (NUM_OF_CHECKERS as i8) + pips.iter().filter(|p| p.is_negative()).sum::<i8>();
+        let on_board = Position {
+            pips,
+            x_off: 0,
+            o_off: 0,
+            checkers: NUM_OF_CHECKERS,
+        };
+        let (x_on_board, o_on_board) = on_board.count_checkers();
 
-        if x_off < 0 {
+        if x_on_board > NUM_OF_CHECKERS {
             Err("Player x has more than 15 checkers on the board.")
-        } else if o_off < 0 {
+        } else if o_on_board > NUM_OF_CHECKERS {
             Err("Player o has more than 15 checkers on the board.")
         } else if pips[X_BAR].is_negative() {
             Err("Index 25 is the bar for player x, number of checkers needs to be positive.")
@@ -219,9 +650,9 @@ impl TryFrom<[i8; 26]> for Position {
             Err("Index 0 is the bar for player o, number of checkers needs to be negative.")
         } else {
             Ok(Position {
-                pips,
-                x_off: x_off as u8,
-                o_off: o_off as u8,
+                x_off: NUM_OF_CHECKERS - x_on_board,
+                o_off: NUM_OF_CHECKERS - o_on_board,
+                ..on_board
             })
         }
     }
@@ -365,6 +796,24 @@ mod tests {
     use crate::pos;
     use crate::position::*;
 
+    #[test]
+    fn game_result_to_i8_and_back() {
+        for result in [WinNormal, WinGammon, WinBg, LoseNormal, LoseGammon, LoseBg] {
+            let value = i8::from(result.clone());
+            assert_eq!(GameResult::try_from(value), Ok(result.clone()));
+            assert_eq!(i8::from(result.reverse()), -value);
+        }
+        assert_eq!(i8::from(WinBg), 3);
+        assert_eq!(i8::from(LoseNormal), -1);
+    }
+
+    #[test]
+    fn game_result_from_invalid_i8() {
+        assert!(GameResult::try_from(0).is_err());
+        assert!(GameResult::try_from(4).is_err());
+        assert!(GameResult::try_from(-4).is_err());
+    }
+
     #[test]
     fn x_off() {
         let given = pos! {x 3:15; o 1:1};
@@ -381,6 +830,89 @@ mod tests {
         assert_eq!(given.o_off(), 5);
     }
 
+    #[test]
+    fn checkers_on_starting_position() {
+        assert_eq!(STARTING.checkers_on(24), 2);
+        assert_eq!(STARTING.checkers_on(13), 5);
+        assert_eq!(STARTING.checkers_on(8), 3);
+        assert_eq!(STARTING.checkers_on(6), 5);
+        assert_eq!(STARTING.checkers_on(1), -2);
+        assert_eq!(STARTING.checkers_on(12), -5);
+        assert_eq!(STARTING.checkers_on(17), -3);
+        assert_eq!(STARTING.checkers_on(19), -5);
+        assert_eq!(STARTING.checkers_on(7), 0);
+        assert_eq!(STARTING.checkers_on(X_BAR), 0);
+        assert_eq!(STARTING.checkers_on(O_BAR), 0);
+        assert_eq!(STARTING.checkers_off(), (0, 0));
+    }
+
+    #[test]
+    fn checkers_on_bar_and_off() {
+        let pos = pos!(x 25:2, 6:3; o 0:1, 19:4);
+        assert_eq!(pos.checkers_on(X_BAR), 2);
+        assert_eq!(pos.checkers_on(O_BAR), -1);
+        assert_eq!(pos.checkers_off(), (10, 10));
+    }
+
+    #[test]
+    fn count_checkers() {
+        assert_eq!(STARTING.count_checkers(), (15, 15));
+        assert_eq!(HYPERGAMMON_STARTING.count_checkers(), (3, 3));
+        assert_eq!(HYPERGAMMON_STARTING.checkers_off(), (0, 0));
+    }
+
+    #[test]
+    fn hypergammon_starting_position() {
+        assert_eq!(HYPERGAMMON_STARTING.checkers_total(), 3);
+        assert_eq!(HYPERGAMMON_STARTING.validate(), Ok(()));
+        assert_eq!(HYPERGAMMON_STARTING.game_state(), Ongoing);
+        assert_eq!(HYPERGAMMON_STARTING.sides_switched(), HYPERGAMMON_STARTING);
+    }
+
+    #[test]
+    fn hypergammon_is_over_after_bearing_off_three_checkers() {
+        // Given
+        let pos = Position {
+            pips: [
+                0, -1, -1, -1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            x_off: 2,
+            o_off: 0,
+            checkers: 3,
+        };
+        let almost_done = Position {
+            pips: [
+                0, -1, -1, -1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
+            ],
+            ..pos
+        };
+        // When
+        let after_moving = almost_done.all_positions_after_moving(&Dice::new(6, 5));
+        // Then
+        assert_eq!(almost_done.game_state(), Ongoing);
+        assert_eq!(after_moving.len(), 1);
+        // `o` has borne off nothing and still has checkers in the home board of `x`.
+        assert_eq!(after_moving[0].game_state(), GameOver(LoseBg));
+        assert_eq!(after_moving[0].count_checkers(), (3, 3));
+    }
+
+    #[test]
+    fn x_is_always_on_roll() {
+        // Given
+        let pos = pos!(x 8:1; o 24:1);
+        // When
+        let after_moving = pos.all_positions_after_moving(&Dice::new(6, 1));
+        // Then
+        // The opponent is on roll after the move, so its single checker on 24 belongs to `x` now.
+        assert_eq!(
+            after_moving,
+            vec![pos!(x 1:1; o 24:1).with_opponent_to_move()]
+        );
+        assert_eq!(after_moving[0].pip(1), 1);
+        assert_eq!(pos.with_opponent_to_move(), pos.sides_switched());
+        assert_eq!(pos.with_opponent_to_move().with_opponent_to_move(), pos);
+    }
+
     #[test]
     fn game_state_bg_when_on_bar() {
         let given = pos!(x 25:1, 1:14; o);
@@ -526,6 +1058,7 @@ mod tests {
             ],
             x_off: 0,
             o_off: 3,
+            checkers: NUM_OF_CHECKERS,
         };
         // When
         let actual = original.sides_switched();
@@ -537,10 +1070,267 @@ mod tests {
             ],
             x_off: 3,
             o_off: 0,
+            checkers: NUM_OF_CHECKERS,
         };
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn validate_legal_positions() {
+        assert_eq!(STARTING.validate(), Ok(()));
+        assert_eq!(pos!(x 1:1; o).validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_illegal_positions() {
+        let too_many_off = Position {
+            x_off: 1,
+            ..STARTING
+        };
+        assert!(too_many_off.validate().is_err());
+        let mut pips = STARTING.pips;
+        pips[X_BAR] = -1;
+        pips[1] = -1;
+        let o_on_x_bar = Position { pips, ..STARTING };
+        assert!(o_on_x_bar.validate().is_err());
+        let both_done = Position {
+            pips: [0; 26],
+            x_off: 15,
+            o_off: 15,
+            checkers: NUM_OF_CHECKERS,
+        };
+        assert!(both_done.validate().is_err());
+    }
+
+    #[test]
+    fn home_board_points_made_full_board() {
+        let given = pos!(x 6:2, 5:2, 4:2, 3:3, 2:2, 1:4; o 24:2, 23:2, 22:2, 21:2, 20:2, 19:5);
+        assert_eq!(given.home_board_points_made(), (6, 6));
+    }
+
+    #[test]
+    fn home_board_points_made_broken_board_with_blot() {
+        let given = pos!(x 13:5, 6:5, 5:1, 4:2, 1:2; o 24:2, 20:3, 19:1, 12:9);
+        assert_eq!(given.home_board_points_made(), (3, 2));
+        assert_eq!(given.sides_switched().home_board_points_made(), (2, 3));
+    }
+
+    #[test]
+    fn pip_counts_starting_position() {
+        assert_eq!(STARTING.pip_counts(), (167, 167));
+    }
+
+    #[test]
+    fn pip_counts_with_checkers_on_the_bar() {
+        let pos = pos!(x X_BAR:1, 3:2; o O_BAR:2, 20:1);
+        assert_eq!(pos.pip_counts(), (31, 55));
+    }
+
+    #[test]
+    fn pip_count_for_each_player_matches_pip_counts() {
+        let pos = pos!(x X_BAR:1, 3:2; o O_BAR:2, 20:1);
+        let (x_pips, o_pips) = pos.pip_counts();
+        assert_eq!(pos.pip_count_for(Player::X), x_pips);
+        assert_eq!(pos.pip_count_for(Player::O), o_pips);
+        assert_eq!(
+            pos.pip_count_for(Player::X) + pos.pip_count_for(Player::O),
+            x_pips + o_pips
+        );
+    }
+
+    #[test]
+    fn diff_of_single_checker_move() {
+        // Given
+        let before = pos!(x 13:2, 6:1; o 20:2);
+        // When
+        let after = before.try_move_single_checker(13, 5).unwrap();
+        let diff = before.diff(&after);
+        // Then
+        assert_eq!(diff.sources, vec![(13, 1)]);
+        assert_eq!(diff.destinations, vec![(8, 1)]);
+        assert!(diff.hits.is_empty());
+    }
+
+    #[test]
+    fn diff_with_hit_and_bear_off() {
+        // Given
+        let before = pos!(x X_BAR:1, 2:2; o 22:1, 20:2);
+        // When
+        let after = pos!(x 22:1, 2:1; o O_BAR:1, 20:2);
+        let diff = before.diff(&after);
+        // Then
+        assert_eq!(diff.sources, vec![(X_BAR, 1), (2, 1)]);
+        assert_eq!(diff.destinations, vec![(22, 1), (0, 1)]);
+        assert_eq!(diff.hits, vec![22]);
+    }
+
+    #[test]
+    fn pip_parity_of_backgame() {
+        // `x` holds the 1 and 3 points of `o`, pip counts are 158 and 83.
+        let backgame = pos!(x 24:2, 22:2, 8:3, 6:4, 5:2, 4:2; o 23:2, 21:3, 20:3, 19:3, 17:2, 16:2);
+        assert_eq!(backgame.pip_parity(), Parity::Odd);
+        let one_pip_less =
+            pos!(x 24:2, 22:2, 8:3, 6:4, 5:1, 4:3; o 23:2, 21:3, 20:3, 19:3, 17:2, 16:2);
+        assert_eq!(one_pip_less.pip_parity(), Parity::Even);
+        assert_eq!(STARTING.pip_parity(), Parity::Even);
+    }
+
+    #[test]
+    fn pip_breakdown_sums_to_pip_counts() {
+        let pos = pos!(x X_BAR:1, 13:2, 6:4, 3:2; o O_BAR:1, 19:5, 12:3, 2:1);
+        let (x_pips, o_pips) = pos.pip_counts();
+        assert_eq!(pos.pip_breakdown(Player::X).iter().sum::<u32>(), x_pips);
+        assert_eq!(pos.pip_breakdown(Player::O).iter().sum::<u32>(), o_pips);
+        assert_eq!(pos.pip_breakdown(Player::X)[24], 25);
+    }
+
+    #[test]
+    fn pip_breakdown_of_six_point_in_starting_position() {
+        assert_eq!(STARTING.pip_breakdown(Player::X)[5], 30);
+        assert_eq!(STARTING.pip_breakdown(Player::O)[5], 30);
+    }
+
+    #[test]
+    fn distance_to_bearoff_starting_position() {
+        assert_eq!(STARTING.distance_to_bearoff(Player::X), 18);
+        assert_eq!(STARTING.distance_to_bearoff(Player::O), 18);
+    }
+
+    #[test]
+    fn distance_to_bearoff_all_checkers_home() {
+        let pos = pos!(x 6:5, 3:10; o O_BAR:1, 20:14);
+        assert_eq!(pos.distance_to_bearoff(Player::X), 0);
+        assert_eq!(pos.distance_to_bearoff(Player::O), 19);
+    }
+
+    #[test]
+    fn game_over_and_winner_of_finished_games() {
+        let lost = pos!(x 18:14; o);
+        assert!(lost.is_game_over());
+        assert_eq!(lost.winner(), Some(Player::O));
+        let won = lost.sides_switched();
+        assert!(won.is_game_over());
+        assert_eq!(won.winner(), Some(Player::X));
+    }
+
+    #[test]
+    fn ongoing_game_has_no_winner() {
+        assert!(!STARTING.is_game_over());
+        assert_eq!(STARTING.winner(), None);
+    }
+
+    #[test]
+    fn loss_conditions_of_normal_loss() {
+        let given = pos!(x 18:14; o);
+        assert!(given.has_borne_off_any(Player::X));
+        assert!(!given.has_checker_in_opponent_home_or_bar(Player::X));
+        assert_eq!(given.game_state(), GameOver(LoseNormal));
+    }
+
+    #[test]
+    fn loss_conditions_of_gammon() {
+        let given = pos!(x 18:15; o);
+        assert!(!given.has_borne_off_any(Player::X));
+        assert!(!given.has_checker_in_opponent_home_or_bar(Player::X));
+        assert_eq!(given.game_state(), GameOver(LoseGammon));
+    }
+
+    #[test]
+    fn loss_conditions_of_backgammon() {
+        let on_bar = pos!(x X_BAR:1, 1:14; o);
+        assert!(!on_bar.has_borne_off_any(Player::X));
+        assert!(on_bar.has_checker_in_opponent_home_or_bar(Player::X));
+        assert_eq!(on_bar.game_state(), GameOver(LoseBg));
+
+        let switched = pos!(x 19:15; o).sides_switched();
+        assert!(!switched.has_borne_off_any(Player::O));
+        assert!(switched.has_checker_in_opponent_home_or_bar(Player::O));
+        assert_eq!(switched.game_state(), GameOver(WinBg));
+    }
+
+    #[test]
+    fn contact_points_in_deep_backgame() {
+        let pos = presets::backgame_1_2();
+        assert_eq!(pos.contact_points(), 15);
+    }
+
+    #[test]
+    fn contact_points_near_race() {
+        let pos = pos!(x 10:3, 6:5, 5:4, 4:3; o 8:1, 19:5, 20:5, 21:4);
+        assert_eq!(pos.contact_points(), 1);
+        assert_eq!(pos!(x 6:2; o O_BAR:1, 19:5).contact_points(), 1);
+    }
+
+    #[test]
+    fn contact_points_in_race_is_zero() {
+        let pos = pos!(x 10:3, 6:5; o 12:1, 19:5);
+        assert_eq!(pos.contact_points(), 0);
+        assert_eq!(pos.game_phase(), GamePhase::Ongoing(Race));
+    }
+
+    #[test]
+    fn race_estimate_with_big_lead() {
+        let pos = pos!(x 3:2; o 10:15);
+        assert!(pos.race_estimate().unwrap() > 0.99);
+        assert!(pos.sides_switched().race_estimate().unwrap() < 0.01);
+    }
+
+    #[test]
+    fn race_estimate_of_even_race_favors_player_on_roll() {
+        let pos = pos!(x 6:5, 5:5, 4:5; o 19:5, 20:5, 21:5);
+        assert_eq!(pos.pip_counts(), (75, 75));
+        let estimate = pos.race_estimate().unwrap();
+        assert!(estimate > 0.55 && estimate < 0.65);
+    }
+
+    #[test]
+    fn race_estimate_with_contact_is_none() {
+        assert_eq!(STARTING.race_estimate(), None);
+    }
+
+    #[test]
+    fn blitz_scores_higher_than_passive_position() {
+        let blitz = pos!(x 13:7, 6:2, 5:2, 4:2, 3:2; o O_BAR:2, 2:1, 1:1, 19:11);
+        assert_eq!(blitz.blitz_score(), 8.0);
+        assert_eq!(STARTING.blitz_score(), 1.0);
+        assert!(blitz.blitz_score() > STARTING.blitz_score());
+    }
+
+    #[test]
+    fn longest_prime_full_prime() {
+        let given = pos!(x 9:2, 8:2, 7:3, 6:2, 5:2, 4:2, 1:2; o 3:1, 24:14);
+        assert_eq!(given.longest_prime(Player::X), 6);
+        assert_eq!(given.longest_prime(Player::O), 1);
+        assert_eq!(given.sides_switched().longest_prime(Player::O), 6);
+    }
+
+    #[test]
+    fn longest_prime_five_prime_with_gap() {
+        // The 4 point is only slotted, so the prime is from 9 to 5.
+        let given = pos!(x 9:2, 8:2, 7:3, 6:2, 5:2, 4:1, 3:3; o 2:1, 20:2, 21:2, 22:2, 24:8);
+        assert_eq!(given.longest_prime(Player::X), 5);
+        assert_eq!(given.longest_prime(Player::O), 3);
+    }
+
+    #[test]
+    fn bearoff_wastage_smooth_distribution_is_low() {
+        let given = pos!(x 6:3, 5:3, 4:3, 3:2, 2:2, 1:2; o 19:3, 20:3, 21:3, 22:2, 23:2, 24:2);
+        assert_eq!(given.bearoff_wastage(), (8, 8));
+    }
+
+    #[test]
+    fn bearoff_wastage_stacked_distribution_is_high() {
+        // Given
+        let stacked = pos!(x 6:2, 2:5, 1:8; o 19:3, 20:3, 21:3, 22:2, 23:2, 24:2);
+        // When
+        let (x_wastage, o_wastage) = stacked.bearoff_wastage();
+        // Then
+        // 21 pips for the low points, 7 for the stacks and 2 for the gaps on the 4 and 5 point.
+        assert_eq!(x_wastage, 30);
+        assert_eq!(o_wastage, 8);
+        assert_eq!(stacked.sides_switched().bearoff_wastage(), (8, 30));
+    }
+
     #[test]
     fn from() {
         let actual = pos!(x X_BAR:2, 3:2, 1:1; o 24:5, 23:4, 22:6);
@@ -550,6 +1340,7 @@ mod tests {
             ],
             x_off: 10,
             o_off: 0,
+            checkers: NUM_OF_CHECKERS,
         };
         assert_eq!(actual, expected);
     }
@@ -718,6 +1509,122 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn all_positions_after_moving_order_is_deterministic() {
+        let mut rng = fastrand::Rng::with_seed(116);
+        let positions: Vec<Position> = (0..50)
+            .map(|_| random_position(&mut rng))
+            .chain([
+                STARTING,
+                presets::backgame_1_2(),
+                presets::bearoff_contact(),
+            ])
+            .collect();
+        for position in positions {
+            // A position which is equal, but was constructed differently.
+            let rebuilt = Position::from_id(&position.position_id());
+            assert_eq!(rebuilt, position);
+            for dice in Dice::all_36() {
+                let first = position.all_positions_after_moving(&dice);
+                assert_eq!(first, position.all_positions_after_moving(&dice));
+                assert_eq!(first, rebuilt.all_positions_after_moving(&dice));
+                let unique: std::collections::HashSet<&Position> = first.iter().collect();
+                assert_eq!(unique.len(), first.len(), "{position:?} {dice:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn all_positions_after_moving_into_reused_buffer_gives_same_positions() {
+        let mut rng = fastrand::Rng::with_seed(121);
+        let mut buffer = Vec::new();
+        for _ in 0..50 {
+            let position = random_position(&mut rng);
+            for dice in Dice::all_36() {
+                position.all_positions_after_moving_into(&dice, &mut buffer);
+                assert_eq!(buffer, position.all_positions_after_moving(&dice));
+            }
+        }
+    }
+
+    /// Random legal position, the game is not over yet.
+    fn random_position(rng: &mut fastrand::Rng) -> Position {
+        let mut pips = [0_i8; 26];
+        let x_on_board = rng.u8(1..=15);
+        for _ in 0..x_on_board {
+            // Mostly on points, sometimes on the bar.
+            let pip = if rng.u8(0..20) == 0 {
+                X_BAR
+            } else {
+                rng.usize(1..25)
+            };
+            pips[pip] += 1;
+        }
+        let o_on_board = rng.u8(1..=15);
+        let mut placed = 0;
+        while placed < o_on_board {
+            let pip = if rng.u8(0..20) == 0 {
+                O_BAR
+            } else {
+                rng.usize(1..25)
+            };
+            if pips[pip] <= 0 {
+                pips[pip] -= 1;
+                placed += 1;
+            }
+        }
+        Position::try_from(pips).unwrap()
+    }
+
+    #[test]
+    fn move_generation_conserves_checkers_and_pips() {
+        let mut rng = fastrand::Rng::with_seed(112);
+        for _ in 0..300 {
+            let before = random_position(&mut rng);
+            for dice in Dice::all_36() {
+                let dice_pips = dice.pips() as i32;
+                let after_moving = before.all_positions_after_moving(&dice);
+                for switched in after_moving.iter() {
+                    let after = switched.sides_switched();
+                    let context = format!("{before:?} {dice:?} {after:?}");
+                    assert_eq!(after.sides_switched(), *switched, "{context}");
+                    assert_eq!(after.count_checkers(), (15, 15), "{context}");
+
+                    // `x` moves forward, but not more than the dice allow.
+                    let x_moved = before.pip_count_for(Player::X) as i32
+                        - after.pip_count_for(Player::X) as i32;
+                    assert!(x_moved >= 0 && x_moved <= dice_pips, "{context}");
+                    assert!(after.x_off() >= before.x_off(), "{context}");
+                    if x_moved == 0 {
+                        assert_eq!(after_moving.len(), 1, "{context}");
+                    }
+
+                    // `o` only loses pips by being hit, each hit checker goes to the bar.
+                    assert_eq!(after.o_off(), before.o_off(), "{context}");
+                    let mut hit_checkers = 0;
+                    let mut hit_pips = 0;
+                    for i in 1..X_BAR {
+                        let lost = (-before.pip(i)).max(0) - (-after.pip(i)).max(0);
+                        assert!(lost >= 0, "{context}");
+                        hit_checkers += lost as i32;
+                        hit_pips += i as i32 * lost as i32;
+                    }
+                    assert_eq!(
+                        after.pip(O_BAR) - before.pip(O_BAR),
+                        -hit_checkers as i8,
+                        "{context}"
+                    );
+                    assert_eq!(
+                        after.pip_count_for(Player::O) as i32
+                            - before.pip_count_for(Player::O) as i32,
+                        hit_pips,
+                        "{context}"
+                    );
+                }
+            }
+        }
+    }
 }
 
 #[cfg(test)]
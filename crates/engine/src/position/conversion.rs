@@ -1,4 +1,4 @@
-use crate::position::{NUM_OF_CHECKERS, O_BAR, Position, X_BAR};
+use crate::position::{O_BAR, Position, X_BAR};
 use base64::Engine;
 use base64::engine::general_purpose;
 use std::collections::HashMap;
@@ -46,6 +46,26 @@ impl Position {
         Position::decode(bytes)
     }
 
+    /// Stable 64-bit key for caching evaluations, for example in a `HashMap<u64, Probabilities>`.
+    ///
+    /// A position is always seen from the player on roll, which is already its canonical form.
+    /// So the key ignores which player of a game is on roll: the same board for the player on roll
+    /// has the same key. It's an FNV-1a hash of the board and of [Position::checkers_total], so it
+    /// doesn't change between runs or Rust versions. Positions of different variants like
+    /// Hypergammon have different keys, even with the same checkers on the board.
+    /// Different positions may collide, although this is very unlikely.
+    pub fn cache_key(&self) -> u64 {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+        const FNV_PRIME: u64 = 0x0100_0000_01b3;
+        self.pips
+            .iter()
+            .map(|&pip| pip as u8)
+            .chain([self.checkers_total()])
+            .fold(FNV_OFFSET_BASIS, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(FNV_PRIME)
+            })
+    }
+
     fn encode(&self) -> [u8; 10] {
         let mut key = [0u8; 10];
         let mut bit_index = 0;
@@ -75,13 +95,9 @@ impl Position {
         let mut bit_index = 0;
         let mut pips = [0i8; 26];
 
-        let mut x_pieces = 0;
-        let mut o_pieces = 0;
-
         (O_BAR..X_BAR).rev().for_each(|point| {
             while (key[bit_index / 8] >> (bit_index % 8)) & 1 == 1 {
                 pips[point] -= 1;
-                o_pieces += 1;
                 bit_index += 1;
             }
             bit_index += 1; // Appending a 0
@@ -90,17 +106,12 @@ impl Position {
         (O_BAR + 1..X_BAR + 1).for_each(|point| {
             while (key[bit_index / 8] >> (bit_index % 8)) & 1 == 1 {
                 pips[point] += 1;
-                x_pieces += 1;
                 bit_index += 1;
             }
             bit_index += 1; // Appending a 0
         });
 
-        Position {
-            pips,
-            x_off: NUM_OF_CHECKERS - x_pieces,
-            o_off: NUM_OF_CHECKERS - o_pieces,
-        }
+        Position::try_from(pips).expect("Position encoding expects at most 15 checkers per player.")
     }
 
     pub fn from_hash_maps(x: &HashMap<usize, u8>, o: &HashMap<usize, u8>) -> Position {
@@ -116,9 +127,57 @@ impl Position {
     }
 }
 
+/// Board and turn of an eXtreme Gammon ID like
+/// `XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10`.
+///
+/// The first field has one character for each bar and point, seen from the "bottom" player:
+/// the top player's bar, the points 1 to 24 and the bottom player's bar. `-` is an empty point,
+/// `A` to `O` are 1 to 15 checkers of the bottom player and `a` to `o` of the top player.
+/// The fourth field is the player on turn: `1` for the bottom player, `-1` for the top player.
+/// The other fields describe the cube, the dice and the score; they are ignored here.
+impl Position {
+    /// Parses the board of an XGID. The returned position is from the perspective of the player on turn.
+    pub fn from_xgid(xgid: &str) -> Result<Position, &'static str> {
+        let mut fields = xgid.strip_prefix("XGID=").unwrap_or(xgid).split(':');
+        let board = fields.next().unwrap_or_default();
+        if board.len() != 26 {
+            return Err("The board of an XGID must have 26 characters.");
+        }
+        let mut pips = [0; 26];
+        for (index, character) in board.bytes().enumerate() {
+            pips[index] = match character {
+                b'-' => 0,
+                b'A'..=b'O' => (character - b'A' + 1) as i8,
+                b'a'..=b'o' => -((character - b'a' + 1) as i8),
+                _ => return Err("The board of an XGID may only contain '-', 'A'-'O' and 'a'-'o'."),
+            };
+        }
+        let position = Position::try_from(pips)?;
+        match fields.nth(2) {
+            None | Some("1") => Ok(position),
+            Some("-1") => Ok(position.sides_switched()),
+            Some(_) => Err("The turn of an XGID must be 1 or -1."),
+        }
+    }
+
+    /// The board of an XGID, with player `x` as the bottom player.
+    pub fn xgid_board(&self) -> String {
+        self.pips
+            .iter()
+            .map(|&pip| match pip {
+                0 => '-',
+                pip if pip > 0 => (b'A' + pip as u8 - 1) as char,
+                pip => (b'a' + pip.unsigned_abs() - 1) as char,
+            })
+            .collect()
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::position::{O_BAR, Position, STARTING, X_BAR};
+    use crate::dice::ALL_21;
+    use crate::position::{HYPERGAMMON_STARTING, O_BAR, Position, STARTING, X_BAR};
+    use std::collections::HashSet;
 
     #[test]
     fn start_id() {
@@ -150,4 +209,65 @@ mod tests {
             assert_eq!(position, Position::from_id(&id));
         }
     }
+
+    #[test]
+    fn cache_key_of_equal_positions() {
+        let pos = pos!(x 24:2, 13:5, 8:3, 6:5; o 1:2, 12:5, 17:3, 19:5);
+        assert_eq!(pos.cache_key(), STARTING.cache_key());
+        let id = STARTING.position_id();
+        assert_eq!(Position::from_id(&id).cache_key(), STARTING.cache_key());
+        let twice_switched = STARTING.sides_switched().sides_switched();
+        assert_eq!(twice_switched.cache_key(), STARTING.cache_key());
+    }
+
+    #[test]
+    fn cache_key_of_different_positions() {
+        // Different rolls can lead to the same position, so they are deduplicated first.
+        let mut positions: HashSet<Position> = ALL_21
+            .iter()
+            .flat_map(|(dice, _)| STARTING.all_positions_after_moving(dice))
+            .collect();
+        positions.insert(STARTING);
+        let keys: HashSet<u64> = positions.iter().map(|pos| pos.cache_key()).collect();
+        assert_eq!(keys.len(), positions.len());
+    }
+
+    #[test]
+    fn cache_key_of_hypergammon_and_identical_standard_layout() {
+        // The same checkers on the board, but the standard position has 12 checkers off per side.
+        let standard = pos!(x 24:1, 23:1, 22:1; o 1:1, 2:1, 3:1);
+        assert_eq!(standard.pips, HYPERGAMMON_STARTING.pips);
+        assert_eq!(standard.checkers_off(), (12, 12));
+        assert_ne!(standard.cache_key(), HYPERGAMMON_STARTING.cache_key());
+    }
+
+    #[test]
+    fn starting_position_from_xgid() {
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10";
+        assert_eq!(Position::from_xgid(xgid), Ok(STARTING));
+        assert_eq!(STARTING.xgid_board(), "-b----E-C---eE---c-e----B-");
+    }
+
+    #[test]
+    fn from_xgid_with_top_player_on_turn() {
+        // Given
+        let xgid = "XGID=-BBBBBC------------bbbbbb-:1:1:-1:00:3:2:0:5:10";
+        // When
+        let position = Position::from_xgid(xgid).unwrap();
+        // Then
+        let bottom = pos!(x 6:3, 5:2, 4:2, 3:2, 2:2, 1:2; o 19:2, 20:2, 21:2, 22:2, 23:2, 24:2);
+        assert_eq!(position, bottom.sides_switched());
+        assert_eq!(position.xgid_board(), "-BBBBBB------------cbbbbb-");
+    }
+
+    #[test]
+    fn from_xgid_errors() {
+        assert!(Position::from_xgid("XGID=-b----E-C---eE---c-e----B:0:0:1:00:0:0:0:0:10").is_err());
+        assert!(
+            Position::from_xgid("XGID=-b----E-C---eE---c-e----Z-:0:0:1:00:0:0:0:0:10").is_err()
+        );
+        assert!(
+            Position::from_xgid("XGID=-b----E-C---eE---c-e----B-:0:0:2:00:0:0:0:0:10").is_err()
+        );
+    }
 }
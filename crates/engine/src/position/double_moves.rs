@@ -1,25 +1,37 @@
-use crate::position::{MOVES_CAPACITY, O_BAR, Position, X_BAR};
+#[cfg(test)]
+use crate::position::MOVES_CAPACITY;
+use crate::position::{O_BAR, Position, X_BAR};
 use std::cmp::min;
 
 impl Position {
     /// Returns a vector of all possible moves when rolling a double.
-    #[inline]
+    #[cfg(test)]
     pub(super) fn all_positions_after_double_move(&self, die: usize) -> Vec<Position> {
+        let mut moves = Vec::with_capacity(MOVES_CAPACITY);
+        self.push_positions_after_double_move(die, &mut moves);
+        moves
+    }
+
+    /// Pushes all possible moves when rolling a double to `moves`, which must be empty.
+    #[inline]
+    pub(super) fn push_positions_after_double_move(&self, die: usize, moves: &mut Vec<Position>) {
+        debug_assert!(moves.is_empty());
         if self.pips[X_BAR] > 0 && self.pips[X_BAR - die] < -1 {
             // Has at least one checker on the bar but can't move it
-            return vec![*self];
+            moves.push(*self);
+            return;
         }
 
         let (position, number_of_entered_checkers) = self.position_after_entering_checkers(die);
         let max_to_move = 4 - number_of_entered_checkers;
         for checkers in (1..max_to_move + 1).rev() {
             // Let's try to use as many dice as possible. If this doesn't work, try with less dice
-            let moves = position.double_moves_after_entering(die, checkers);
+            position.double_moves_after_entering(die, checkers, moves);
             if !moves.is_empty() {
-                return moves;
+                return;
             }
         }
-        vec![position]
+        moves.push(position);
     }
 
     /// Returns the position after entering all possible checkers and the number of entered checkers (0 to 4)
@@ -42,10 +54,14 @@ impl Position {
         (position, number_of_checkers_to_enter as u32)
     }
 
-    /// Returns a vector of all possible moves after entering the checkers from the bar.
+    /// Pushes all possible moves after entering the checkers from the bar to `moves`.
     /// It takes into account the number of checkers that can be moved (1 to 4).
-    fn double_moves_after_entering(&self, die: usize, nr_movable_checkers: u32) -> Vec<Position> {
-        let mut moves: Vec<Position> = Vec::with_capacity(MOVES_CAPACITY);
+    fn double_moves_after_entering(
+        &self,
+        die: usize,
+        nr_movable_checkers: u32,
+        moves: &mut Vec<Position>,
+    ) {
         (self.smallest_pip_to_check(die)..X_BAR).for_each(|i1| {
             if self.can_move_when_bearoff_is_legal(i1, die) {
                 let pos = self.clone_and_move_single_checker(i1, die);
@@ -79,7 +95,6 @@ impl Position {
                 });
             }
         });
-        moves
     }
 }
 
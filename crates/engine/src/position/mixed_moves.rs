@@ -1,25 +1,40 @@
 use crate::dice::MixedDice;
-use crate::position::{MOVES_CAPACITY, O_BAR, Position, X_BAR};
+#[cfg(test)]
+use crate::position::MOVES_CAPACITY;
+use crate::position::{O_BAR, Position, X_BAR};
 use std::cmp::max;
 
 impl Position {
-    /// Returns all legal positions after rolling a double and then moving.
+    /// Returns all legal positions after rolling mixed dice and then moving.
     /// The return values have not switched sides yet.
-    #[inline]
+    #[cfg(test)]
     pub(super) fn all_positions_after_mixed_move(&self, dice: &MixedDice) -> Vec<Position> {
+        let mut moves = Vec::with_capacity(MOVES_CAPACITY);
+        self.push_positions_after_mixed_move(dice, &mut moves);
+        moves
+    }
+
+    /// Pushes all legal positions after rolling mixed dice and then moving to `moves`,
+    /// which must be empty. The positions have not switched sides yet.
+    #[inline]
+    pub(super) fn push_positions_after_mixed_move(
+        &self,
+        dice: &MixedDice,
+        moves: &mut Vec<Position>,
+    ) {
         debug_assert!(dice.big > dice.small);
+        debug_assert!(moves.is_empty());
         match self.pips[X_BAR] {
-            0 => self.moves_with_0_checkers_on_bar(dice),
-            1 => self.moves_with_1_checker_on_bar(dice),
-            _ => self.moves_with_2_checkers_on_bar(dice),
+            0 => self.moves_with_0_checkers_on_bar(dice, moves),
+            1 => self.moves_with_1_checker_on_bar(dice, moves),
+            _ => self.moves_with_2_checkers_on_bar(dice, moves),
         }
     }
 
     /// Mixed moves with exactly 1 checker on the bar.
-    fn moves_with_1_checker_on_bar(&self, dice: &MixedDice) -> Vec<Position> {
+    fn moves_with_1_checker_on_bar(&self, dice: &MixedDice, moves: &mut Vec<Position>) {
         debug_assert!(self.pips[X_BAR] == 1);
 
-        let mut moves: Vec<Position> = Vec::with_capacity(MOVES_CAPACITY);
         let mut enter_big: Option<Position> = None;
         let mut enter_small: Option<Position> = None;
 
@@ -63,28 +78,26 @@ impl Position {
                 moves.push(*self);
             }
         }
-        moves
     }
 
     /// Mixed moves with no checkers on the bar.
-    fn moves_with_0_checkers_on_bar(&self, dice: &MixedDice) -> Vec<Position> {
+    fn moves_with_0_checkers_on_bar(&self, dice: &MixedDice, moves: &mut Vec<Position>) {
         debug_assert!(self.pips[X_BAR] == 0);
 
         // Let's try to find moves where both dice are used.
-        let mut moves = self.two_checker_moves(dice);
+        self.two_checker_moves(dice, moves);
         if moves.is_empty() {
             // No moves found with both dice used, so let's try the bigger die only.
-            self.one_checker_moves(dice.big, &mut moves);
+            self.one_checker_moves(dice.big, moves);
             if moves.is_empty() {
                 // No moves found with the bigger die used, so let's try the smaller one.
-                self.one_checker_moves(dice.small, &mut moves);
+                self.one_checker_moves(dice.small, moves);
                 if moves.is_empty() {
                     // The player can't move any checker, so we return the identical position.
                     moves.push(*self);
                 }
             }
         }
-        moves
     }
 
     /// All positions after moving a single checker once. If no move is possible it returns `None`.
@@ -101,11 +114,9 @@ impl Position {
     }
 
     // All moves with no checkers on the bar where two checkers can be moved.
-    fn two_checker_moves(&self, dice: &MixedDice) -> Vec<Position> {
+    fn two_checker_moves(&self, dice: &MixedDice, moves: &mut Vec<Position>) {
         debug_assert!(self.pips[X_BAR] == 0);
 
-        let mut moves: Vec<Position> = Vec::with_capacity(MOVES_CAPACITY);
-
         // All moves where the `small` die is moved first
         (self.smallest_pip_to_check(dice.small)..X_BAR)
             .rev()
@@ -153,12 +164,10 @@ impl Position {
                 }
             });
         }
-
-        moves
     }
 
     /// All moves (well, exactly one) when at least two checkers are on the bar.
-    fn moves_with_2_checkers_on_bar(&self, dice: &MixedDice) -> Vec<Position> {
+    fn moves_with_2_checkers_on_bar(&self, dice: &MixedDice, moves: &mut Vec<Position>) {
         debug_assert!(self.pips[X_BAR] > 1);
 
         let mut position = *self;
@@ -168,7 +177,7 @@ impl Position {
         if position.can_enter(dice.small) {
             position.enter_single_checker(dice.small);
         }
-        vec![position]
+        moves.push(position);
     }
 
     fn can_enter(&self, die: usize) -> bool {
@@ -208,7 +217,7 @@ impl Position {
 
 #[cfg(test)]
 mod tests {
-    use crate::dice::MixedDice;
+    use crate::dice::{Dice, MixedDice};
     use crate::pos;
     use crate::position::{O_BAR, X_BAR};
 
@@ -383,6 +392,59 @@ mod tests {
         assert_eq!(resulting_positions, vec![expected]);
     }
 
+    #[test]
+    fn either_die_but_not_both_forces_bigger_die() {
+        // Given
+        // 13/7 and 13/9 are both legal, but afterwards the other die would land on the blocked 3.
+        let position = pos!(x 13:1; o 3:2);
+        // When
+        let resulting_positions = position.all_positions_after_moving(&Dice::new(6, 4));
+        // Then
+        let expected = pos!(x 7:1; o 3:2);
+        assert_eq!(resulting_positions, vec![expected.sides_switched()]);
+    }
+
+    #[test]
+    fn overshooting_die_bears_off_from_highest_point() {
+        // Given
+        let position = pos!(x 4:1, 3:1; o 20:1);
+        // When
+        let resulting_positions = position.all_positions_after_moving(&Dice::new(6, 1));
+        // Then
+        assert_eq!(
+            position.try_move_single_checker(4, 6),
+            Some(pos!(x 3:1; o 20:1))
+        );
+        // 4/off 3/2 in either order or 4/3 3/off.
+        let expected = [
+            pos!(x 2:1; o 20:1).sides_switched(),
+            pos!(x 3:1; o 20:1).sides_switched(),
+        ];
+        assert_eq!(resulting_positions.len(), expected.len());
+        assert!(expected.iter().all(|p| resulting_positions.contains(p)));
+    }
+
+    #[test]
+    fn overshooting_die_cant_bear_off_with_checker_on_higher_point() {
+        // Given
+        let position = pos!(x 5:1, 4:1; o 20:1);
+        // When
+        let resulting_positions = position.all_positions_after_moving(&Dice::new(6, 1));
+        // Then
+        assert_eq!(position.try_move_single_checker(4, 6), None);
+        assert_eq!(
+            position.try_move_single_checker(5, 6),
+            Some(pos!(x 4:1; o 20:1))
+        );
+        // The 6 bears off the checker from the 5, unless the 1 has moved it to the 4 before.
+        let expected = [
+            pos!(x 3:1; o 20:1).sides_switched(),
+            pos!(x 4:1; o 20:1).sides_switched(),
+        ];
+        assert_eq!(resulting_positions.len(), expected.len());
+        assert!(expected.iter().all(|p| resulting_positions.contains(p)));
+    }
+
     #[test]
     fn forced_smaller_die_first_then_bear_off() {
         // Given
@@ -0,0 +1,53 @@
+//! Typical positions, for example as fixtures in tests of several crates.
+//!
+//! All positions are from the perspective of player `x`, who is on roll.
+
+use crate::pos;
+use crate::position::Position;
+
+/// Player `x` is bearing off while `o` still holds an anchor in `x`'s home board.
+pub fn bearoff_contact() -> Position {
+    pos!(x 6:4, 5:3, 4:3, 2:2, 1:1; o 3:2, 19:4, 20:3, 21:3, 22:2, 23:1)
+}
+
+/// Both players hold an anchor on the opponent's five point and keep their midpoints.
+pub fn mutual_holding_game() -> Position {
+    pos!(x 20:2, 13:4, 8:3, 6:4, 4:2; o 5:2, 12:4, 17:3, 19:4, 21:2)
+}
+
+/// Player `x` holds the one and the two point in `o`'s home board, `o` is far ahead in the race.
+pub fn backgame_1_2() -> Position {
+    pos!(x 24:2, 23:2, 13:3, 8:2, 6:4, 5:2; o 22:2, 21:3, 20:3, 19:3, 18:2, 7:2)
+}
+
+/// Player `x` has two checkers on the bar and `o` has made all six points of the home board.
+pub fn closeout_on_bar() -> Position {
+    pos!(x 25:2, 13:5, 8:4, 6:4; o 24:3, 23:2, 22:2, 21:2, 20:2, 19:2, 12:2)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::Dice;
+    use crate::position::GameState::Ongoing;
+    use crate::position::presets::*;
+
+    #[test]
+    fn all_presets_are_valid() {
+        for preset in [
+            bearoff_contact(),
+            mutual_holding_game(),
+            backgame_1_2(),
+            closeout_on_bar(),
+        ] {
+            assert_eq!(preset.validate(), Ok(()));
+            assert_eq!(preset.game_state(), Ongoing);
+        }
+    }
+
+    #[test]
+    fn closeout_on_bar_cant_move() {
+        let pos = closeout_on_bar();
+        let after_moving = pos.all_positions_after_moving(&Dice::new(6, 5));
+        assert_eq!(after_moving, vec![pos.sides_switched()]);
+    }
+}
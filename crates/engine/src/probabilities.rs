@@ -1,7 +1,9 @@
 use crate::position::GameResult;
 use crate::position::GameResult::*;
+use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Formatter;
+use std::ops::{Add, Mul};
 
 /// Sum of all six fields will always be 1.0
 #[derive(Clone, Default, PartialEq)]
@@ -50,10 +52,16 @@ impl Probabilities {
         "win_normal;win_gammon;win_bg;lose_normal;lose_gammon;lose_bg".to_string()
     }
 
+    /// Probability to win the game in any way: `win_normal + win_gammon + win_bg`.
     pub fn win(&self) -> f32 {
         self.win_normal + self.win_gammon + self.win_bg
     }
 
+    /// Probability to lose the game in any way: `lose_normal + lose_gammon + lose_bg`.
+    pub fn lose(&self) -> f32 {
+        self.lose_normal + self.lose_gammon + self.lose_bg
+    }
+
     /// Returns the probabilities from the opponent's perspective (wins and losses swapped).
     pub fn switch_sides(&self) -> Self {
         Self {
@@ -67,10 +75,193 @@ impl Probabilities {
     }
 
     /// Cubeless equity
+    ///
+    /// Each win or loss counts one point, a gammon one more and a backgammon two more.
     pub fn equity(&self) -> f32 {
-        self.win_normal - self.lose_normal
-            + 2.0 * (self.win_gammon - self.lose_gammon)
-            + 3.0 * (self.win_bg - self.lose_bg)
+        self.win() - self.lose()
+            + (self.win_gammon - self.lose_gammon)
+            + 2.0 * (self.win_bg - self.lose_bg)
+    }
+
+    /// Cubeless equity from the opponent's perspective, the same as `switch_sides().equity()`.
+    pub fn equity_opponent(&self) -> f32 {
+        -self.equity()
+    }
+
+    /// Cubeless equity in millipoints, like GNU Backgammon shows equities with three decimals.
+    ///
+    /// The sign is the same as in [Probabilities::equity]: positive values are good for player `x`.
+    /// The equity is multiplied by 1000 and rounded half away from zero, so `-0.0625` becomes `-63`.
+    pub fn equity_millipoints(&self) -> i32 {
+        (self.equity() * 1000.0).round() as i32
+    }
+
+    /// Value of a gammon-go score, usable as the `value` of [Evaluator::best_position](crate::evaluator::Evaluator::best_position)
+    /// like `evaluator.best_position(&pos, &dice, Probabilities::gammon_go_value)`.
+    ///
+    /// This is for study positions, where a win with a gammon is worth much more than a single
+    /// win and any loss is equally bad, for example when trailing 2-away 1-away post-Crawford
+    /// without using the cube. Each win counts `1`, a gammon or backgammon counts twice, losses
+    /// count `0`. For real match play use the match equity table instead.
+    pub fn gammon_go_value(&self) -> f32 {
+        self.win() + self.win_gammon + self.win_bg
+    }
+
+    /// Moves the gammons and backgammons that `x` wins into `win_normal`.
+    ///
+    /// For match scores at which gammons are worthless for `x` only, like when `x` is 1-away.
+    /// The winning probability stays the same.
+    pub fn zero_win_gammons(&self) -> Self {
+        Self {
+            win_normal: self.win(),
+            win_gammon: 0.0,
+            win_bg: 0.0,
+            ..self.clone()
+        }
+    }
+
+    /// Moves the gammons and backgammons that `x` loses into `lose_normal`, see [Probabilities::zero_win_gammons].
+    pub fn zero_lose_gammons(&self) -> Self {
+        Self {
+            lose_normal: self.lose(),
+            lose_gammon: 0.0,
+            lose_bg: 0.0,
+            ..self.clone()
+        }
+    }
+
+    /// Compares by cubeless equity. A `NaN` equity is lower than any other equity,
+    /// so when sorting the best probabilities first, broken evaluations end up last.
+    pub fn cmp_by_equity(&self, other: &Self) -> Ordering {
+        cmp_nan_lowest(self.equity(), other.equity())
+    }
+
+    /// Cubeless equity with custom points for a normal win, a gammon and a backgammon.
+    ///
+    /// The standard values are `1`, `2` and `3`, see [Probabilities::equity].
+    pub fn equity_with_values(&self, normal: f32, gammon: f32, bg: f32) -> f32 {
+        normal * (self.win_normal - self.lose_normal)
+            + gammon * (self.win_gammon - self.lose_gammon)
+            + bg * (self.win_bg - self.lose_bg)
+    }
+
+    /// Scales all six fields so that their sum is `1.0` again, for example after blending
+    /// probabilities with `+` and `*`. If the sum is `0`, all fields stay `0`.
+    pub fn normalize(&self) -> Self {
+        let sum = self.win() + self.lose();
+        if sum > 0.0 {
+            self.clone() * (1.0 / sum)
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Cleans up noisy outputs of a neural net: each field is clamped to `[0, 1]`, then the fields
+    /// are scaled so that their sum is `1.0` again.
+    ///
+    /// If all fields are `0` after clamping (or `NaN`), it's a 50/50 split of normal wins and losses.
+    pub fn clamp01_and_renormalize(&self) -> Self {
+        // `f32::clamp` keeps `NaN`, so it's turned into `0` first.
+        let clamp = |value: f32| {
+            if value.is_nan() {
+                0.0
+            } else {
+                value.clamp(0.0, 1.0)
+            }
+        };
+        let clamped = Self {
+            win_normal: clamp(self.win_normal),
+            win_gammon: clamp(self.win_gammon),
+            win_bg: clamp(self.win_bg),
+            lose_normal: clamp(self.lose_normal),
+            lose_gammon: clamp(self.lose_gammon),
+            lose_bg: clamp(self.lose_bg),
+        };
+        let sum = clamped.win() + clamped.lose();
+        if sum > 0.0 {
+            clamped.normalize()
+        } else {
+            Self {
+                win_normal: 0.5,
+                lose_normal: 0.5,
+                ..Default::default()
+            }
+        }
+    }
+
+    /// Splits up the probabilities into winning chances and conditional gammon/backgammon chances.
+    ///
+    /// If the game can't be won (or lost), the conditional probabilities for that side are `0`.
+    pub fn breakdown(&self) -> Breakdown {
+        let conditional = |probability: f32, given: f32| {
+            if given > 0.0 {
+                probability / given
+            } else {
+                0.0
+            }
+        };
+        let win = self.win();
+        let lose = self.lose();
+        Breakdown {
+            win,
+            win_gammon: conditional(self.win_gammon + self.win_bg, win),
+            win_bg: conditional(self.win_bg, win),
+            lose,
+            lose_gammon: conditional(self.lose_gammon + self.lose_bg, lose),
+            lose_bg: conditional(self.lose_bg, lose),
+        }
+    }
+}
+
+/// Probabilities split up the way GnuBG and most UIs display them:
+/// The chance to win or lose and how many of those games end with a gammon or backgammon.
+///
+/// Unlike in [Probabilities], gammons include backgammons here.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Breakdown {
+    /// Probability to win the game.
+    pub win: f32,
+    /// Probability to win a gammon or backgammon, given that the game is won.
+    pub win_gammon: f32,
+    /// Probability to win a backgammon, given that the game is won.
+    pub win_bg: f32,
+    /// Probability to lose the game.
+    pub lose: f32,
+    /// Probability to lose a gammon or backgammon, given that the game is lost.
+    pub lose_gammon: f32,
+    /// Probability to lose a backgammon, given that the game is lost.
+    pub lose_bg: f32,
+}
+
+/// Field-wise sum, mainly for blending probabilities. The result has to be normalized.
+impl Add for Probabilities {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            win_normal: self.win_normal + other.win_normal,
+            win_gammon: self.win_gammon + other.win_gammon,
+            win_bg: self.win_bg + other.win_bg,
+            lose_normal: self.lose_normal + other.lose_normal,
+            lose_gammon: self.lose_gammon + other.lose_gammon,
+            lose_bg: self.lose_bg + other.lose_bg,
+        }
+    }
+}
+
+/// Multiplies each field with a scalar, for example a weight when blending probabilities.
+impl Mul<f32> for Probabilities {
+    type Output = Self;
+
+    fn mul(self, factor: f32) -> Self {
+        Self {
+            win_normal: self.win_normal * factor,
+            win_gammon: self.win_gammon * factor,
+            win_bg: self.win_bg * factor,
+            lose_normal: self.lose_normal * factor,
+            lose_gammon: self.lose_gammon * factor,
+            lose_bg: self.lose_bg * factor,
+        }
     }
 }
 
@@ -87,6 +278,31 @@ impl From<[f32; 6]> for Probabilities {
     }
 }
 
+impl TryFrom<&[f32]> for Probabilities {
+    type Error = &'static str;
+
+    /// Typically used for the outputs of a neural net, in the same order as [Probabilities::csv_header].
+    ///
+    /// Returns an error if the slice doesn't have six values or if they don't sum up to `1.0`.
+    fn try_from(value: &[f32]) -> Result<Self, Self::Error> {
+        let array: [f32; 6] = value
+            .try_into()
+            .map_err(|_| "Probabilities need exactly six values.")?;
+        let sum: f32 = array.iter().sum();
+        if (sum - 1.0).abs() > 0.001 {
+            return Err("The sum of the probabilities must be 1.0.");
+        }
+        Ok(Self::from(array))
+    }
+}
+
+impl From<[u32; 6]> for Probabilities {
+    /// Counts of the six results, like in a [ResultCounter].
+    fn from(value: [u32; 6]) -> Self {
+        Self::from(&ResultCounter { results: value })
+    }
+}
+
 impl From<&ResultCounter> for Probabilities {
     /// Typically used from rollouts.
     fn from(value: &ResultCounter) -> Self {
@@ -184,12 +400,23 @@ impl ResultCounter {
     }
 }
 
+/// Like [f32::total_cmp], but `NaN` is lower than any other value, regardless of its sign.
+pub fn cmp_nan_lowest(a: f32, b: f32) -> Ordering {
+    match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => a.total_cmp(&b),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::position::GameResult::{
         LoseBg, LoseGammon, LoseNormal, WinBg, WinGammon, WinNormal,
     };
     use crate::probabilities::{Probabilities, ResultCounter};
+    use std::cmp::Ordering;
 
     #[test]
     fn from_array() {
@@ -205,6 +432,38 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn try_from_valid_slice() {
+        let values = vec![0.04, 0.06, 0.1, 0.2, 0.25, 0.35];
+        let actual = Probabilities::try_from(values.as_slice());
+        assert_eq!(
+            actual,
+            Ok(Probabilities::from([0.04, 0.06, 0.1, 0.2, 0.25, 0.35]))
+        );
+    }
+
+    #[test]
+    fn try_from_slice_with_wrong_length() {
+        let values = [0.5, 0.1, 0.1, 0.3];
+        let actual = Probabilities::try_from(&values[..]);
+        assert_eq!(actual, Err("Probabilities need exactly six values."));
+    }
+
+    #[test]
+    fn try_from_slice_with_wrong_sum() {
+        let values = [0.5, 0.1, 0.1, 0.3, 0.1, 0.1];
+        let actual = Probabilities::try_from(&values[..]);
+        assert_eq!(actual, Err("The sum of the probabilities must be 1.0."));
+    }
+
+    #[test]
+    fn from_counts_is_like_result_counter() {
+        let counter = ResultCounter::new(0, 1, 3, 4, 8, 16);
+        let actual = Probabilities::from([0, 1, 3, 4, 8, 16]);
+        assert_eq!(actual, Probabilities::from(&counter));
+        assert_eq!(actual.lose_bg, 0.5);
+    }
+
     #[test]
     fn from_game_result() {
         assert_eq!(Probabilities::from(WinNormal).equity(), 1.0);
@@ -250,6 +509,64 @@ mod tests {
         );
     }
 
+    #[test]
+    fn breakdown_conditional_probabilities() {
+        // Given
+        let probabilities: Probabilities = [0.4, 0.1, 0.05, 0.3, 0.1, 0.05].into();
+        // When
+        let breakdown = probabilities.breakdown();
+        // Then
+        let assert_close = |actual: f32, expected: f32| {
+            assert!(
+                (actual - expected).abs() < 0.00001,
+                "{actual} != {expected}"
+            )
+        };
+        assert_close(breakdown.win, 0.55);
+        assert_close(breakdown.win_gammon, 0.15 / 0.55);
+        assert_close(breakdown.win_bg, 0.05 / 0.55);
+        assert_close(breakdown.lose, 0.45);
+        assert_close(breakdown.lose_gammon, 0.15 / 0.45);
+        assert_close(breakdown.lose_bg, 0.05 / 0.45);
+    }
+
+    #[test]
+    fn breakdown_certain_win() {
+        let probabilities: Probabilities = [0.0, 1.0, 0.0, 0.0, 0.0, 0.0].into();
+        let breakdown = probabilities.breakdown();
+        assert_eq!(breakdown.win, 1.0);
+        assert_eq!(breakdown.win_gammon, 1.0);
+        assert_eq!(breakdown.win_bg, 0.0);
+        assert_eq!(breakdown.lose, 0.0);
+        assert_eq!(breakdown.lose_gammon, 0.0);
+        assert_eq!(breakdown.lose_bg, 0.0);
+    }
+
+    #[test]
+    fn cmp_by_equity_treats_nan_as_lowest() {
+        let good: Probabilities = [0.6, 0.1, 0.0, 0.3, 0.0, 0.0].into();
+        let bad: Probabilities = [0.2, 0.0, 0.0, 0.6, 0.2, 0.0].into();
+        let nan: Probabilities = [f32::NAN, 0.0, 0.0, 1.0, 0.0, 0.0].into();
+        assert_eq!(good.cmp_by_equity(&bad), Ordering::Greater);
+        assert_eq!(bad.cmp_by_equity(&nan), Ordering::Greater);
+        assert_eq!(nan.cmp_by_equity(&good), Ordering::Less);
+        assert_eq!(nan.cmp_by_equity(&nan), Ordering::Equal);
+    }
+
+    #[test]
+    fn equity_with_default_values_is_equity() {
+        let probabilities: Probabilities = [0.32, 0.26, 0.12, 0.15, 0.1, 0.05].into();
+        let difference = probabilities.equity_with_values(1.0, 2.0, 3.0) - probabilities.equity();
+        assert!(difference.abs() < 0.000001);
+    }
+
+    #[test]
+    fn equity_with_values_gammons_count_single() {
+        let probabilities: Probabilities = [0.2, 0.3, 0.1, 0.2, 0.1, 0.1].into();
+        let equity = probabilities.equity_with_values(1.0, 1.0, 1.0);
+        assert_eq!(equity, probabilities.win() - 0.4);
+    }
+
     #[test]
     fn equity_win_normal() {
         let probabilities = Probabilities {
@@ -341,6 +658,97 @@ mod tests {
         assert_eq!(probabilities.equity(), 0.0);
     }
 
+    #[test]
+    fn win_and_lose_sum_up_to_one() {
+        let probabilities = Probabilities::from([0.38, 0.2, 0.1, 0.12, 0.15, 0.05]);
+        assert!((probabilities.win() - 0.68).abs() < 1e-6);
+        assert!((probabilities.lose() - 0.32).abs() < 1e-6);
+        assert!((probabilities.win() + probabilities.lose() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn equity_opponent_is_equity_after_switching_sides() {
+        let probabilities = Probabilities::from([0.38, 0.2, 0.1, 0.12, 0.15, 0.05]);
+        assert_eq!(probabilities.equity_opponent(), -probabilities.equity());
+        assert_eq!(
+            probabilities.switch_sides().equity(),
+            probabilities.equity_opponent()
+        );
+        assert_eq!(probabilities.switch_sides().switch_sides(), probabilities);
+    }
+
+    #[test]
+    fn equity_millipoints_like_gnubg() {
+        // GNU Backgammon shows cumulative probabilities, here 55.1% wins, 17.4% gammons and 0.8%
+        // backgammons against 12.2% gammons and 0.6% backgammons. It shows the cubeless equity
+        // `2 * 0.551 - 1 + 0.174 - 0.122 + 0.008 - 0.006` as +0.156.
+        let probabilities = Probabilities {
+            win_normal: 0.551 - 0.174,
+            win_gammon: 0.174 - 0.008,
+            win_bg: 0.008,
+            lose_normal: 0.449 - 0.122,
+            lose_gammon: 0.122 - 0.006,
+            lose_bg: 0.006,
+        };
+        assert_eq!(probabilities.equity_millipoints(), 156);
+        assert_eq!(probabilities.switch_sides().equity_millipoints(), -156);
+    }
+
+    #[test]
+    fn equity_millipoints_rounds_half_away_from_zero() {
+        let probabilities = Probabilities {
+            win_normal: 0.46875,
+            lose_normal: 0.53125,
+            ..Default::default()
+        };
+        assert_eq!(probabilities.equity(), -0.0625);
+        assert_eq!(probabilities.equity_millipoints(), -63);
+    }
+
+    #[test]
+    fn clamp01_and_renormalize_negative_value() {
+        let probabilities = Probabilities {
+            win_normal: 0.5,
+            win_gammon: -0.02,
+            win_bg: 0.0,
+            lose_normal: 0.5,
+            lose_gammon: 0.0,
+            lose_bg: 0.0,
+        };
+        let cleaned = probabilities.clamp01_and_renormalize();
+        assert_eq!(cleaned.win_gammon, 0.0);
+        assert_eq!(cleaned.win_normal, 0.5);
+        assert_eq!(cleaned.lose_normal, 0.5);
+    }
+
+    #[test]
+    fn clamp01_and_renormalize_all_zero() {
+        let cleaned = Probabilities::default().clamp01_and_renormalize();
+        assert_eq!(cleaned, [0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        let nan = Probabilities {
+            win_normal: f32::NAN,
+            ..Default::default()
+        };
+        assert_eq!(nan.clamp01_and_renormalize(), cleaned);
+    }
+
+    #[test]
+    fn clamp01_and_renormalize_sum_over_one() {
+        let probabilities = Probabilities {
+            win_normal: 1.2,
+            win_gammon: 0.3,
+            win_bg: 0.0,
+            lose_normal: 0.5,
+            lose_gammon: 0.0,
+            lose_bg: 0.0,
+        };
+        let cleaned = probabilities.clamp01_and_renormalize();
+        // After clamping the sum is 1.8.
+        assert!((cleaned.win_normal - 1.0 / 1.8).abs() < 1e-6);
+        assert!((cleaned.win_gammon - 0.3 / 1.8).abs() < 1e-6);
+        assert!((cleaned.lose_normal - 0.5 / 1.8).abs() < 1e-6);
+    }
+
     #[test]
     fn win() {
         let probabilities = Probabilities {
@@ -353,4 +761,55 @@ mod tests {
         };
         assert_eq!(probabilities.win(), 0.82);
     }
+
+    #[test]
+    fn zero_win_gammons_keeps_win_but_lowers_equity() {
+        // Given
+        let probabilities: Probabilities = [0.4, 0.2, 0.05, 0.25, 0.1, 0.0].into();
+        // When
+        let zeroed = probabilities.zero_win_gammons();
+        // Then
+        assert_eq!(zeroed.win(), probabilities.win());
+        assert_eq!(zeroed.win_gammon, 0.0);
+        assert_eq!(zeroed.win_bg, 0.0);
+        assert_eq!(zeroed.lose_gammon, 0.1);
+        assert!(zeroed.equity() < probabilities.equity());
+    }
+
+    #[test]
+    fn zero_lose_gammons_keeps_losses_but_raises_equity() {
+        let probabilities: Probabilities = [0.4, 0.2, 0.05, 0.25, 0.1, 0.0].into();
+        let zeroed = probabilities.zero_lose_gammons();
+        assert!((zeroed.lose_normal - 0.35).abs() < 1e-6);
+        assert_eq!(zeroed.lose_gammon, 0.0);
+        assert_eq!(zeroed.win_gammon, 0.2);
+        assert!(zeroed.equity() > probabilities.equity());
+    }
+
+    #[test]
+    fn blend_and_normalize() {
+        // Given
+        let p: Probabilities = [0.5, 0.1, 0.0, 0.3, 0.1, 0.0].into();
+        let q: Probabilities = [0.2, 0.1, 0.1, 0.4, 0.1, 0.1].into();
+        // When
+        let mean = (p * 0.5 + q * 0.5).normalize();
+        // Then
+        let expected: Probabilities = [0.35, 0.1, 0.05, 0.35, 0.1, 0.05].into();
+        assert!((mean.equity() - expected.equity()).abs() < 1e-6);
+        assert!((mean.win_normal - expected.win_normal).abs() < 1e-6);
+        assert!((mean.lose_bg - expected.lose_bg).abs() < 1e-6);
+        let sum = mean.win() + mean.lose();
+        assert!((sum - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn normalize_scales_to_one() {
+        let doubled: Probabilities = [0.8, 0.2, 0.0, 0.6, 0.4, 0.0].into();
+        let normalized = doubled.normalize();
+        assert_eq!(normalized, [0.4, 0.1, 0.0, 0.3, 0.2, 0.0].into());
+        assert_eq!(
+            Probabilities::default().normalize(),
+            Probabilities::default()
+        );
+    }
 }
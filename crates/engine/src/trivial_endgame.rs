@@ -0,0 +1,151 @@
+use crate::dice::ALL_21;
+use crate::evaluator::PartialEvaluator;
+use crate::position::{O_BAR, Position, X_BAR};
+use crate::probabilities::Probabilities;
+
+/// With a single checker, each roll moves at least 3 pips. So 24 pips take at most 8 rolls.
+const MAX_ROLLS: usize = 8;
+
+/// Exact evaluation of the simplest endgame: each player has at most one checker left and the two
+/// checkers have passed each other, so it's a pure race.
+///
+/// A single checker can always use the whole roll, so only the pip counts matter. As both players
+/// have already borne off 14 checkers, there are no gammons.
+pub struct TrivialEndgame {
+    /// `rolls[pips][k]` is the probability that a single checker `pips` away needs exactly `k` rolls.
+    rolls: [[f64; MAX_ROLLS + 1]; X_BAR],
+}
+
+impl Default for TrivialEndgame {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEvaluator for TrivialEndgame {
+    /// Returns `None` unless both players have at most one checker left, neither on the bar
+    /// and without contact.
+    fn try_eval(&self, pos: &Position) -> Option<Probabilities> {
+        if pos.pip(X_BAR) > 0 || pos.pip(O_BAR) < 0 {
+            return None;
+        }
+        let x_pips = single_checker((1..X_BAR).rev().map(|i| (i, pos.pip(i))))?;
+        let o_pips = single_checker((O_BAR + 1..X_BAR).map(|i| (X_BAR - i, -pos.pip(i))))?;
+        if x_pips > 0 && o_pips > 0 && x_pips + o_pips >= X_BAR {
+            // The checkers haven't passed each other yet.
+            return None;
+        }
+        let win = self.win_probability(x_pips, o_pips);
+        Some(Probabilities {
+            win_normal: win,
+            lose_normal: 1.0 - win,
+            ..Default::default()
+        })
+    }
+}
+
+impl TrivialEndgame {
+    pub fn new() -> Self {
+        let mut rolls = [[0.0; MAX_ROLLS + 1]; X_BAR];
+        rolls[0][0] = 1.0;
+        for pips in 1..X_BAR {
+            for (dice, amount) in ALL_21.iter() {
+                let probability = *amount as f64 / 36.0;
+                let moved = dice.pips() as usize;
+                if moved >= pips {
+                    rolls[pips][1] += probability;
+                } else {
+                    for k in 1..=MAX_ROLLS {
+                        rolls[pips][k] += probability * rolls[pips - moved][k - 1];
+                    }
+                }
+            }
+        }
+        Self { rolls }
+    }
+
+    /// `x` is on roll, so `x` wins if it needs at most as many rolls as `o`.
+    fn win_probability(&self, x_pips: usize, o_pips: usize) -> f32 {
+        let x_rolls = &self.rolls[x_pips];
+        let o_rolls = &self.rolls[o_pips];
+        let win: f64 = (0..=MAX_ROLLS)
+            .map(|k| x_rolls[k] * o_rolls[k..].iter().sum::<f64>())
+            .sum();
+        win.clamp(0.0, 1.0) as f32
+    }
+}
+
+/// Pips of the single checker left on the board, `0` if it has been borne off.
+/// `None` if there is more than one checker.
+///
+/// `checkers` yields the distance to bear off together with the number of checkers there.
+fn single_checker(mut checkers: impl Iterator<Item = (usize, i8)>) -> Option<usize> {
+    let mut found = None;
+    for (pips, number) in checkers.by_ref() {
+        match number {
+            n if n <= 0 => {}
+            1 if found.is_none() => found = Some(pips),
+            _ => return None,
+        }
+    }
+    Some(found.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bearoff::BearoffDatabase;
+    use crate::evaluator::PartialEvaluator;
+    use crate::pos;
+    use crate::trivial_endgame::TrivialEndgame;
+
+    #[test]
+    fn one_pip_against_one_pip_wins_on_roll() {
+        let probabilities = TrivialEndgame::new()
+            .try_eval(&pos!(x 1:1; o 24:1))
+            .unwrap();
+        assert_eq!(probabilities.win_normal, 1.0);
+        assert_eq!(probabilities.lose_normal, 0.0);
+    }
+
+    #[test]
+    fn two_pips_against_one_pip_wins_on_roll() {
+        // Every roll moves at least 3 pips.
+        let probabilities = TrivialEndgame::new()
+            .try_eval(&pos!(x 2:1; o 24:1))
+            .unwrap();
+        assert_eq!(probabilities.win_normal, 1.0);
+        assert_eq!(probabilities.win_gammon, 0.0);
+    }
+
+    #[test]
+    fn outside_the_home_board() {
+        // 18 mixed rolls and 5 doubles (22 to 66) move at least 7 pips.
+        let probabilities = TrivialEndgame::new()
+            .try_eval(&pos!(x 7:1; o 24:1))
+            .unwrap();
+        assert!((probabilities.win_normal - 23.0 / 36.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn same_as_bearoff_database() {
+        let endgame = TrivialEndgame::new();
+        let database = BearoffDatabase::generate(1);
+        for x in 1..=6 {
+            for o in 19..=24 {
+                let pos = pos!(x x:1; o o:1);
+                let expected = database.try_eval(&pos).unwrap();
+                let actual = endgame.try_eval(&pos).unwrap();
+                assert!((actual.win_normal - expected.win_normal).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn none_for_more_checkers_contact_or_bar() {
+        let endgame = TrivialEndgame::new();
+        assert_eq!(endgame.try_eval(&pos!(x 6:2; o 24:1)), None);
+        assert_eq!(endgame.try_eval(&pos!(x 6:1; o 23:1, 24:1)), None);
+        assert_eq!(endgame.try_eval(&pos!(x 20:1; o 10:1)), None);
+        assert_eq!(endgame.try_eval(&pos!(x 25:1; o 24:1)), None);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::evaluator::{Evaluator, EvaluatorInfo};
+use crate::position::Position;
+use crate::probabilities::Probabilities;
+
+/// Ignores gammons and backgammons, for example in a 1-point match or in the last game of a match.
+///
+/// All winning chances of the wrapped evaluator are moved into `win_normal`, all losing chances into
+/// `lose_normal`. So the equity of the returned probabilities is `2 * win - 1` and ranking moves by
+/// equity is the same as ranking them by [Probabilities::win].
+pub struct WinOnlyEvaluator<T: Evaluator> {
+    pub evaluator: T,
+}
+
+impl<T: Evaluator> WinOnlyEvaluator<T> {
+    fn win_only(probabilities: Probabilities) -> Probabilities {
+        probabilities.zero_win_gammons().zero_lose_gammons()
+    }
+}
+
+impl<T: Evaluator> Evaluator for WinOnlyEvaluator<T> {
+    fn eval(&self, pos: &Position) -> Probabilities {
+        Self::win_only(self.evaluator.eval(pos))
+    }
+
+    fn eval_batch(&self, positions: Vec<Position>) -> Vec<(Position, Probabilities)> {
+        self.evaluator
+            .eval_batch(positions)
+            .into_iter()
+            .map(|(position, probabilities)| (position, Self::win_only(probabilities)))
+            .collect()
+    }
+
+    fn eval_slice(&self, positions: &[Position]) -> Vec<(Position, Probabilities)> {
+        self.evaluator
+            .eval_slice(positions)
+            .into_iter()
+            .map(|(position, probabilities)| (position, Self::win_only(probabilities)))
+            .collect()
+    }
+
+    fn info(&self) -> EvaluatorInfo {
+        self.evaluator.info()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::dice::Dice;
+    use crate::evaluator::{Evaluator, EvaluatorFake};
+    use crate::pos;
+    use crate::win_only::WinOnlyEvaluator;
+
+    #[test]
+    fn equity_is_two_times_win_minus_one() {
+        // Given
+        let probabilities = [0.3, 0.2, 0.1, 0.2, 0.15, 0.05].into();
+        let evaluator = WinOnlyEvaluator {
+            evaluator: EvaluatorFake::with_default(probabilities),
+        };
+        // When
+        let win_only = evaluator.eval(&pos!(x 7:2; o 20:2));
+        // Then
+        assert!((win_only.win() - 0.6).abs() < 1e-6);
+        assert!((win_only.equity() - (2.0 * 0.6 - 1.0)).abs() < 1e-6);
+        assert_eq!(win_only.win_gammon + win_only.lose_gammon, 0.0);
+    }
+
+    #[test]
+    fn best_position_by_equity_maximizes_win() {
+        // Given
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut fake = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        fake.insert(
+            pos!(x 5:1, 3:1; o 20:2).sides_switched(),
+            [0.5, 0.1, 0.1, 0.1, 0.1, 0.1].into(),
+        );
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        let expected = fake.best_position(&pos, &dice, |p| p.win());
+        assert_ne!(expected, fake.best_position_by_equity(&pos, &dice));
+        let evaluator = WinOnlyEvaluator { evaluator: fake };
+        // When
+        let best = evaluator.best_position_by_equity(&pos, &dice);
+        // Then
+        assert_eq!(best, expected);
+    }
+}
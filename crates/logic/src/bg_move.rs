@@ -3,6 +3,7 @@ use engine::position::Position;
 #[cfg(feature = "web")]
 use serde::Serialize;
 use std::cmp::max;
+use std::fmt;
 #[cfg(feature = "web")]
 use utoipa::ToSchema;
 
@@ -14,6 +15,9 @@ mod mixed;
 #[derive(Debug, PartialEq)]
 pub struct BgMove {
     pub(crate) details: Vec<MoveDetail>,
+    /// Points on which an opponent's blot was hit, from the perspective of the moving player.
+    /// Sorted from bigger to smaller pips, like `details`.
+    pub(crate) hits: Vec<usize>,
 }
 
 #[derive(Debug, PartialEq)]
@@ -47,6 +51,20 @@ impl BgMove {
         self.details
     }
 
+    /// Points on which an opponent's blot was hit, from the perspective of the moving player.
+    #[inline]
+    pub fn hits(&self) -> &[usize] {
+        &self.hits
+    }
+
+    /// How many pips the opponent loses because of the hits of this move.
+    ///
+    /// A blot hit on our point `i` is `25 - i` pips away from the opponent's home and has
+    /// to start again from the bar, so it loses `i` pips.
+    pub fn pips_sent_back(&self) -> u32 {
+        self.hits.iter().map(|&pip| pip as u32).sum()
+    }
+
     pub fn new(old: &Position, new: &Position, dice: &Dice) -> BgMove {
         match dice {
             Dice::Mixed(dice) => Self::new_mixed(old, new, dice),
@@ -54,6 +72,12 @@ impl BgMove {
         }
     }
 
+    fn with_details(old: &Position, new: &Position, details: Vec<MoveDetail>) -> BgMove {
+        // A blot can only disappear by being hit, even if the hitting checker moved on.
+        let hits = old.diff(new).hits;
+        BgMove { details, hits }
+    }
+
     /// Finds up to two pips where `more[pip] > less[pip]`.
     /// Only looks at positive pips, so it ignores the opponent.
     /// If `less[pip]` is negative (the opponent had a checker there), it will treat at 0, not as -1.
@@ -74,10 +98,34 @@ impl BgMove {
     }
 }
 
+/// Standard notation like `24/18 13/11`, `bar/20` or `6/off`. A move without any checker
+/// movement is written as `pass`.
+impl fmt::Display for BgMove {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.details.is_empty() {
+            return write!(f, "pass");
+        }
+        for (i, detail) in self.details.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            match detail.from {
+                25 => write!(f, "bar/")?,
+                from => write!(f, "{from}/")?,
+            }
+            match detail.to {
+                0 => write!(f, "off")?,
+                to => write!(f, "{to}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::bg_move::{BgMove, MoveDetail};
-    use engine::dice::MixedDice;
+    use engine::dice::{Dice, MixedDice};
     use engine::pos;
 
     #[test]
@@ -136,4 +184,73 @@ mod tests {
             vec![MoveDetail { from: 4, to: 2 }, MoveDetail { from: 2, to: 0 },]
         );
     }
+
+    #[test]
+    fn display_uses_standard_notation() {
+        // Given
+        let bg_move = BgMove {
+            details: vec![
+                MoveDetail { from: 25, to: 20 },
+                MoveDetail { from: 13, to: 11 },
+                MoveDetail { from: 4, to: 0 },
+            ],
+            hits: vec![],
+        };
+        // When
+        let notation = bg_move.to_string();
+        // Then
+        assert_eq!(notation, "bar/20 13/11 4/off");
+        let pass = BgMove {
+            details: vec![],
+            hits: vec![],
+        };
+        assert_eq!(pass.to_string(), "pass");
+    }
+
+    #[test]
+    fn single_hit() {
+        // Given
+        let old = pos!(x 13:1, 8:1; o 6:1, 19:2);
+        let new = old
+            .try_move_single_checker(13, 5)
+            .and_then(|pos| pos.try_move_single_checker(8, 2))
+            .unwrap();
+        // When
+        let bg_move = BgMove::new(&old, &new, &Dice::new(5, 2));
+        // Then
+        assert_eq!(bg_move.hits(), &[6]);
+        assert_eq!(bg_move.pips_sent_back(), 6);
+    }
+
+    #[test]
+    fn double_hit_on_double_roll() {
+        // Given
+        let old = pos!(x 13:2; o 11:1, 9:1, 1:2);
+        let new = old
+            .try_move_single_checker(13, 2)
+            .and_then(|pos| pos.try_move_single_checker(11, 2))
+            .and_then(|pos| pos.try_move_single_checker(13, 2))
+            .and_then(|pos| pos.try_move_single_checker(11, 2))
+            .unwrap();
+        // When
+        let bg_move = BgMove::new(&old, &new, &Dice::new(2, 2));
+        // Then
+        assert_eq!(bg_move.hits(), &[11, 9]);
+        assert_eq!(bg_move.pips_sent_back(), 20);
+    }
+
+    #[test]
+    fn no_hit() {
+        // Given
+        let old = pos!(x 13:1, 8:1; o 6:2, 19:2);
+        let new = old
+            .try_move_single_checker(13, 2)
+            .and_then(|pos| pos.try_move_single_checker(8, 5))
+            .unwrap();
+        // When
+        let bg_move = BgMove::new(&old, &new, &Dice::new(5, 2));
+        // Then
+        assert!(bg_move.hits().is_empty());
+        assert_eq!(bg_move.pips_sent_back(), 0);
+    }
 }
@@ -16,7 +16,7 @@ impl BgMove {
                 index -= 1
             }
         }
-        BgMove { details }
+        BgMove::with_details(old, new, details)
     }
 }
 
@@ -17,18 +17,17 @@ impl BgMove {
                     {
                         // In case of bear off, we want 0, not negative numbers
                         let to = from_pip.saturating_sub(die1 + die2);
-                        return BgMove {
-                            details: vec![
-                                MoveDetail {
-                                    from: from_pip,
-                                    to: from_pip - die1,
-                                },
-                                MoveDetail {
-                                    from: from_pip - die1,
-                                    to,
-                                },
-                            ],
-                        };
+                        let details = vec![
+                            MoveDetail {
+                                from: from_pip,
+                                to: from_pip - die1,
+                            },
+                            MoveDetail {
+                                from: from_pip - die1,
+                                to,
+                            },
+                        ];
+                        return BgMove::with_details(old, new, details);
                     }
                 }
                 // We couldn't find one checker being moved twice. So it must have been a checker only moved once.
@@ -37,9 +36,8 @@ impl BgMove {
                         && position == *new
                     {
                         let to = from_pip.saturating_sub(die);
-                        return BgMove {
-                            details: vec![MoveDetail { from: from_pip, to }],
-                        };
+                        let details = vec![MoveDetail { from: from_pip, to }];
+                        return BgMove::with_details(old, new, details);
                     }
                 }
                 panic!("One of the previous if/else branches should have returned something.");
@@ -73,7 +71,7 @@ impl BgMove {
                 ]
             }
         };
-        BgMove { details }
+        BgMove::with_details(old, new, details)
     }
 }
 
@@ -26,27 +26,127 @@ pub enum CubePosition {
 }
 
 /// The current state of the doubling cube.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub struct CubeState {
     /// Who owns the cube.
     pub position: CubePosition,
-    /// Current cube value (1, 2, 4, …). Only relevant in match play; a money
-    /// game is linear, so the value does not change the decision there.
-    pub value: u32,
+    /// How often the cube has been turned; the cube value is `2^level`.
+    /// Storing the exponent instead of the value means the cube can't overflow.
+    pub level: u8,
 }
 
-impl Default for CubeState {
-    fn default() -> Self {
+impl CubeState {
+    /// Builds the cube state from a cube value (1, 2, 4, …).
+    pub fn with_value(position: CubePosition, value: u32) -> Result<Self, &'static str> {
+        if !value.is_power_of_two() || value > MAX_CUBE_VALUE {
+            return Err("cube_value must be a positive power of two: 1, 2, 4, …");
+        }
+        let level = value.trailing_zeros() as u8;
+        Ok(Self { position, level })
+    }
+
+    /// Current cube value (1, 2, 4, …), saturating at `u32::MAX`. Only relevant in match play;
+    /// a money game is linear, so the value does not change the decision there.
+    #[inline]
+    pub fn value(&self) -> u32 {
+        1u32.checked_shl(self.level.into()).unwrap_or(u32::MAX)
+    }
+
+    /// The cube after player `x` doubled and the opponent took.
+    ///
+    /// Returns an error if `x` may not double or the cube is already at its highest level.
+    pub fn doubled(&self) -> Result<Self, &'static str> {
+        if !can_double(self.position) {
+            return Err(
+                "only the owner of the cube or either player of a centered cube may double",
+            );
+        }
+        let level = self
+            .level
+            .checked_add(1)
+            .ok_or("the cube can't be turned any further")?;
+        Ok(Self {
+            position: CubePosition::OpponentOwned,
+            level,
+        })
+    }
+
+    /// The same cube seen from the opponent's perspective.
+    #[inline]
+    pub fn sides_switched(&self) -> Self {
+        let position = match self.position {
+            CubePosition::Centered => CubePosition::Centered,
+            CubePosition::Owned => CubePosition::OpponentOwned,
+            CubePosition::OpponentOwned => CubePosition::Owned,
+        };
         Self {
-            position: CubePosition::Centered,
-            value: 1,
+            position,
+            level: self.level,
+        }
+    }
+}
+
+/// The doubling cube over the course of a game.
+///
+/// Unlike [`CubeState`], which is a snapshot for a single decision, this remembers every turn
+/// of the cube. That's needed for rules like Jacoby, which ask whether the cube was ever turned.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cube {
+    state: CubeState,
+    /// The cube before each turn, oldest first.
+    history: Vec<CubeState>,
+}
+
+impl Cube {
+    #[inline]
+    pub fn state(&self) -> CubeState {
+        self.state
+    }
+
+    #[inline]
+    pub fn level(&self) -> u8 {
+        self.state.level
+    }
+
+    #[inline]
+    pub fn value(&self) -> u32 {
+        self.state.value()
+    }
+
+    /// Player `x` doubles and the opponent takes. See [`CubeState::doubled`].
+    pub fn double(&mut self) -> Result<(), &'static str> {
+        let doubled = self.state.doubled()?;
+        self.history.push(self.state);
+        self.state = doubled;
+        Ok(())
+    }
+
+    #[inline]
+    pub fn was_turned(&self) -> bool {
+        !self.history.is_empty()
+    }
+
+    /// The cube before each turn, oldest first, seen from the current perspective.
+    #[inline]
+    pub fn history(&self) -> &[CubeState] {
+        &self.history
+    }
+
+    /// The same cube and history seen from the opponent's perspective.
+    pub fn sides_switched(&self) -> Self {
+        Self {
+            state: self.state.sides_switched(),
+            history: self.history.iter().map(|s| s.sides_switched()).collect(),
         }
     }
 }
 
-/// Largest doubling cube value the match cube math will consider. Real cubes are
+/// Largest doubling cube level the match cube math will consider. Real cubes are
 /// far below this; the bound only keeps the take-point recursion from overflowing.
-pub const MAX_CUBE_VALUE: u32 = 1 << 20;
+pub const MAX_CUBE_LEVEL: u8 = 20;
+
+/// Doubling cube value at [`MAX_CUBE_LEVEL`].
+pub const MAX_CUBE_VALUE: u32 = 1 << MAX_CUBE_LEVEL;
 
 /// The scoring context for a cube decision.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -110,6 +210,14 @@ fn can_double(position: CubePosition) -> bool {
 pub struct CubeInfo {
     /// `true` if the player `x` should double, `false` if no double yet or too good.
     double: bool,
+    /// `true` if the opponent would pass a double, but playing on for gammons is worth even more.
+    /// Not part of the web API, which only has `double` and `accept`.
+    #[cfg_attr(feature = "web", serde(skip))]
+    too_good: bool,
+    /// `true` if the opponent should beaver a double: take and immediately redouble, keeping the cube.
+    /// Only in money games. Not part of the web API.
+    #[cfg_attr(feature = "web", serde(skip))]
+    beaver: bool,
     /// `true` if the opponent should take the cube, `false` if they should reject.
     accept: bool,
     /// Cubeless money game equity of the position, from player `x`'s perspective.
@@ -139,52 +247,26 @@ impl CubeInfo {
     pub fn new(value: &Probabilities, cube_position: CubePosition) -> Self {
         let x = CUBE_EFFICIENCY;
 
-        // Probability of winning and losing (cubeless).
-        let p = value.win();
-        let q = 1.0 - p;
-
-        // Average points won given a win (`w`) and lost given a loss (`l`).
-        // Both are positive and lie in `[1, 3]`. Guard against division by zero
-        // for certain wins or losses, in which case that side never happens.
-        let w = if p > 0.0 {
-            (value.win_normal + 2.0 * value.win_gammon + 3.0 * value.win_bg) / p
-        } else {
-            0.0
-        };
-        let l = if q > 0.0 {
-            (value.lose_normal + 2.0 * value.lose_gammon + 3.0 * value.lose_bg) / q
-        } else {
-            0.0
-        };
-
-        // Janowski's cubeful equities from `x`'s perspective for a cube of value 1,
-        // one for each possible cube position.
-        let common = p * (w + l + 0.5 * x) - l;
-        // Centered cube: neither player has doubled yet.
-        let equity_centered = (4.0 / (4.0 - x)) * (common - 0.25 * x);
-        // Player `x` owns the cube.
-        let equity_owned = common;
-        // The opponent owns the cube (also the state after `x` doubles and it is taken).
-        let equity_opponent_owned = common - 0.5 * x;
-
         // Equity if `x` does not double, depending on who owns the cube.
-        let equity_no_double = match cube_position {
-            CubePosition::Centered => equity_centered,
-            CubePosition::Owned => equity_owned,
-            CubePosition::OpponentOwned => equity_opponent_owned,
-        };
+        let equity_no_double = value.cubeful_equity(cube_position, x);
         // After a double and take the opponent owns the cube and the stake is doubled.
-        let equity_double_take = 2.0 * equity_opponent_owned;
+        let equity_double_take = 2.0 * value.cubeful_equity(CubePosition::OpponentOwned, x);
         // If the opponent passes, `x` cashes the current stake, worth +1.0 per point.
         let equity_pass = 1.0;
 
-        Self::decide(
-            value.equity(),
-            equity_no_double,
-            equity_pass,
-            equity_double_take,
-            can_double(cube_position),
-        )
+        // The opponent beavers when they are the favorite after taking, so a double would be a mistake.
+        let beaver = can_double(cube_position) && equity_double_take < 0.0;
+        Self {
+            beaver,
+            ..Self::decide(
+                value.equity(),
+                equity_no_double,
+                equity_pass,
+                equity_double_take,
+                value.equity(),
+                can_double(cube_position),
+            )
+        }
     }
 
     /// Match play cube decision using the live-cube model (recursive live take
@@ -200,7 +282,7 @@ impl CubeInfo {
         let cubeless_equity = value.equity();
         // Clamp inputs so the take-point recursion always terminates and cannot
         // overflow, whatever a caller passes (`match_equity` clamps aways too).
-        let stake = cube.value.clamp(1, MAX_CUBE_VALUE);
+        let stake = cube.value().min(MAX_CUBE_VALUE);
         let x_away = x_away.min(MAX_AWAY);
         let o_away = o_away.min(MAX_AWAY);
 
@@ -216,6 +298,8 @@ impl CubeInfo {
             let (double, accept) = post_crawford_decision(x_away, can_double(cube.position));
             return Self {
                 double,
+                too_good: false,
+                beaver: false,
                 accept,
                 cubeless_equity,
                 equity_no_double: position_equity(value, x_away, o_away, stake),
@@ -237,11 +321,14 @@ impl CubeInfo {
         let equity_double_take = equity_opponent_can_cash(value, x_away, o_away, 2 * stake);
         // If the opponent passes, `x` cashes the current stake.
         let equity_pass = match_equity_after_win(x_away, o_away, stake);
+        // When `x` is too good to double, the game is played on for the current stake.
+        let equity_play_on = position_equity(value, x_away, o_away, stake);
         Self::decide(
             cubeless_equity,
             equity_no_double,
             equity_pass,
             equity_double_take,
+            equity_play_on,
             can_double(cube.position),
         )
     }
@@ -250,22 +337,29 @@ impl CubeInfo {
     /// not doubling (`equity_no_double`), the opponent passing (`equity_pass`)
     /// and the opponent taking (`equity_double_take`). Works for money game
     /// (points) and match play (match-winning probabilities) alike.
+    ///
+    /// `equity_play_on` is the cubeless equity at the current stake, including gammons. It decides
+    /// whether a position that the opponent would pass is rather too good to double.
     fn decide(
         cubeless_equity: f32,
         equity_no_double: f32,
         equity_pass: f32,
         equity_double_take: f32,
+        equity_play_on: f32,
         can_double: bool,
     ) -> Self {
         // The opponent picks the response that is worst for `x`.
         let equity_double = equity_pass.min(equity_double_take);
         // The opponent takes when taking is better for them than passing.
         let accept = can_double && equity_double_take < equity_pass;
-        // `x` doubles when doubling beats not doubling. "Too good" positions,
-        // where playing on is worth more than cashing, fall out automatically.
-        let double = can_double && equity_double > equity_no_double;
+        // Beyond the opponent's take point, `x` only plays on when the gammons are worth more
+        // than cashing. Otherwise it's a double and pass.
+        let too_good = can_double && !accept && equity_play_on > equity_pass;
+        let double = can_double && !too_good && (!accept || equity_double > equity_no_double);
         Self {
             double,
+            too_good,
+            beaver: false,
             accept,
             cubeless_equity,
             equity_no_double,
@@ -277,6 +371,8 @@ impl CubeInfo {
     fn no_cube(cubeless_equity: f32, equity_no_double: f32, equity_double_take: f32) -> Self {
         Self {
             double: false,
+            too_good: false,
+            beaver: false,
             accept: false,
             cubeless_equity,
             equity_no_double,
@@ -285,6 +381,133 @@ impl CubeInfo {
     }
 }
 
+/// Cubeful money game equity of [Probabilities].
+pub trait CubefulEquity {
+    /// Janowski's cubeful equity for a cube of value 1, from the perspective of player `x`.
+    ///
+    /// Owning the cube is worth more than a centered cube, because only `x` can redouble and cash
+    /// at the opponent's take point. When the opponent owns the cube, it's worth less.
+    /// `efficiency` is the cube efficiency between `0.0` (dead cube, the same as the cubeless
+    /// equity) and `1.0` (fully live cube), `2/3` is typical for a money game.
+    fn cubeful_equity(&self, cube_position: CubePosition, efficiency: f32) -> f32;
+
+    /// Cubeful equity in points when `x` owns the cube at `cube_level`, so after the opponent
+    /// doubled and `x` took. The cube value is `2^cube_level`.
+    ///
+    /// Owning the cube includes the recube vig: `x` may redouble at the right moment and cash.
+    /// That's why this is higher than the equity with a centered cube of the same value.
+    fn holding_cube_equity(&self, cube_level: u8, efficiency: f32) -> f32 {
+        let cube = CubeState {
+            position: CubePosition::Owned,
+            level: cube_level,
+        };
+        cube.value() as f32 * self.cubeful_equity(CubePosition::Owned, efficiency)
+    }
+}
+
+impl CubefulEquity for Probabilities {
+    fn cubeful_equity(&self, cube_position: CubePosition, efficiency: f32) -> f32 {
+        let x = efficiency;
+
+        // Probability of winning and losing (cubeless).
+        let p = self.win();
+        let q = 1.0 - p;
+
+        // Average points won given a win (`w`) and lost given a loss (`l`).
+        // Both are positive and lie in `[1, 3]`. Guard against division by zero
+        // for certain wins or losses, in which case that side never happens.
+        let w = if p > 0.0 {
+            (self.win_normal + 2.0 * self.win_gammon + 3.0 * self.win_bg) / p
+        } else {
+            0.0
+        };
+        let l = if q > 0.0 {
+            (self.lose_normal + 2.0 * self.lose_gammon + 3.0 * self.lose_bg) / q
+        } else {
+            0.0
+        };
+
+        // Janowski's cubeful equities from `x`'s perspective for a cube of value 1.
+        let common = p * (w + l + 0.5 * x) - l;
+        match cube_position {
+            // Neither player has doubled yet.
+            CubePosition::Centered => (4.0 / (4.0 - x)) * (common - 0.25 * x),
+            // Only `x` may redouble.
+            CubePosition::Owned => common,
+            // Also the state after `x` doubles and it is taken.
+            CubePosition::OpponentOwned => common - 0.5 * x,
+        }
+    }
+}
+
+/// Cube decision of both players, see [CubeInfo::cube_action].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeAction {
+    /// `x` should not double (yet), or can't double because of the cube position or score.
+    NoDouble,
+    /// `x` should double and the opponent should take.
+    DoubleTake,
+    /// `x` should double and the opponent should pass.
+    DoublePass,
+    /// The opponent would pass, but `x` wins enough gammons to rather play on for more.
+    TooGoodToDouble,
+}
+
+/// Equities of the three cube actions, for example from a cubeful rollout of a position.
+///
+/// All equities are from the perspective of player `x` who considers doubling, in the same unit
+/// (points in money games or match winning chances in match play).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubeRolloutResult {
+    pub equity_no_double: f32,
+    pub equity_double_take: f32,
+    pub equity_double_pass: f32,
+}
+
+/// Recommended cube action together with the cost of the wrong actions.
+///
+/// Errors are never negative, the error of the recommended action is `0.0`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CubeDecision {
+    pub action: CubeAction,
+    /// Equity `x` loses by not doubling.
+    pub no_double_error: f32,
+    /// Equity `x` loses by doubling, assuming the opponent responds correctly.
+    pub double_error: f32,
+    /// Equity the opponent loses by choosing the wrong response to a double.
+    pub response_error: f32,
+}
+
+impl CubeRolloutResult {
+    /// Applies the standard decision tree to the three equities.
+    ///
+    /// The opponent takes if taking is better for them than passing. `x` doubles if the equity after
+    /// that response is higher than without doubling. When the opponent would pass but not
+    /// doubling is worth even more than cashing, `x` is too good to double.
+    pub fn recommendation(&self) -> CubeDecision {
+        let take = self.equity_double_take < self.equity_double_pass;
+        let equity_double = self.equity_double_take.min(self.equity_double_pass);
+        let best = self.equity_no_double.max(equity_double);
+        let action = if equity_double > self.equity_no_double {
+            if take {
+                CubeAction::DoubleTake
+            } else {
+                CubeAction::DoublePass
+            }
+        } else if !take && self.equity_no_double > self.equity_double_pass {
+            CubeAction::TooGoodToDouble
+        } else {
+            CubeAction::NoDouble
+        };
+        CubeDecision {
+            action,
+            no_double_error: best - self.equity_no_double,
+            double_error: best - equity_double,
+            response_error: (self.equity_double_pass - self.equity_double_take).abs(),
+        }
+    }
+}
+
 /// Match-winning probability for `x` given `x` wins the game, playing at cube
 /// value `v` (gammon-weighted). This is the `p → 1` endpoint of the equity line.
 fn win_equity(value: &Probabilities, a: u32, b: u32, v: u32) -> f32 {
@@ -399,9 +622,25 @@ impl CubeInfo {
     pub fn double(&self) -> bool {
         self.double
     }
+    /// The cube decision of both players combined.
+    pub fn cube_action(&self) -> CubeAction {
+        match (self.too_good, self.double, self.accept) {
+            (true, _, _) => CubeAction::TooGoodToDouble,
+            (false, true, true) => CubeAction::DoubleTake,
+            (false, true, false) => CubeAction::DoublePass,
+            (false, false, _) => CubeAction::NoDouble,
+        }
+    }
     pub fn accept(&self) -> bool {
         self.accept
     }
+    /// `true` if the opponent should answer a double with a beaver, only in money games.
+    ///
+    /// After a beaver the opponent owns the cube at four times the stake. This is right whenever
+    /// the opponent's equity after taking is positive, so `x` shouldn't have doubled at all.
+    pub fn should_beaver(&self) -> bool {
+        self.beaver
+    }
     pub fn cubeless_equity(&self) -> f32 {
         self.cubeless_equity
     }
@@ -415,7 +654,10 @@ impl CubeInfo {
 
 #[cfg(test)]
 mod tests {
-    use super::{CubeInfo, CubePosition, CubeState, MatchState};
+    use super::{
+        Cube, CubeAction, CubeInfo, CubePosition, CubeRolloutResult, CubeState, CubefulEquity,
+        MatchState,
+    };
     use engine::probabilities::Probabilities;
 
     /// Helper for a position without gammons or backgammons and a given win probability.
@@ -468,6 +710,64 @@ mod tests {
         assert!(cube.accept());
     }
 
+    #[test]
+    fn owning_the_cube_is_worth_more_than_centered() {
+        let probs = Probabilities {
+            win_normal: 0.45,
+            win_gammon: 0.15,
+            win_bg: 0.01,
+            lose_normal: 0.3,
+            lose_gammon: 0.08,
+            lose_bg: 0.01,
+        };
+        let owned = probs.cubeful_equity(CubePosition::Owned, 2.0 / 3.0);
+        let centered = probs.cubeful_equity(CubePosition::Centered, 2.0 / 3.0);
+        let opponent_owned = probs.cubeful_equity(CubePosition::OpponentOwned, 2.0 / 3.0);
+        assert!(owned > centered);
+        assert!(centered > opponent_owned);
+    }
+
+    #[test]
+    fn holding_cube_is_worth_more_than_centered_cube() {
+        let probs = no_gammons(0.6);
+        let holding = probs.holding_cube_equity(1, 2.0 / 3.0);
+        let centered = 2.0 * probs.cubeful_equity(CubePosition::Centered, 2.0 / 3.0);
+        assert!(holding > centered);
+        assert_eq!(holding, 2.0 * probs.holding_cube_equity(0, 2.0 / 3.0));
+        assert!((probs.holding_cube_equity(2, 0.0) - 4.0 * probs.equity()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn dead_cube_is_cubeless_equity() {
+        let probs = no_gammons(0.6);
+        for position in [
+            CubePosition::Centered,
+            CubePosition::Owned,
+            CubePosition::OpponentOwned,
+        ] {
+            assert!((probs.cubeful_equity(position, 0.0) - probs.equity()).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn beaver_when_doubler_is_underdog() {
+        // Given `x` is an underdog with 45% wins, but doubles anyway.
+        let cube = CubeInfo::from(&no_gammons(0.45));
+        // Then the opponent should take and beaver.
+        assert!(!cube.double());
+        assert!(cube.accept());
+        assert!(cube.should_beaver());
+    }
+
+    #[test]
+    fn no_beaver_after_correct_double() {
+        let cube = CubeInfo::from(&no_gammons(0.70));
+        assert!(cube.accept());
+        assert!(!cube.should_beaver());
+        let cube = CubeInfo::new(&no_gammons(0.45), CubePosition::OpponentOwned);
+        assert!(!cube.should_beaver());
+    }
+
     #[test]
     fn too_good_to_double() {
         // Given a position that wins a lot of gammons and backgammons,
@@ -486,6 +786,81 @@ mod tests {
         assert!(!cube.double());
     }
 
+    #[test]
+    fn cube_action_too_good_to_double_with_high_gammon_rate() {
+        // Given an almost certain win with lots of gammons
+        let probs = Probabilities {
+            win_normal: 0.3,
+            win_gammon: 0.65,
+            win_bg: 0.0,
+            lose_normal: 0.05,
+            lose_gammon: 0.0,
+            lose_bg: 0.0,
+        };
+        // When
+        let cube = CubeInfo::from(&probs);
+        // Then the opponent would pass, but playing on for the gammon is worth more.
+        assert!(!cube.accept());
+        assert_eq!(cube.cube_action(), CubeAction::TooGoodToDouble);
+    }
+
+    #[test]
+    fn cube_action_double_pass_without_gammons() {
+        // Given an almost certain win without any gammons, there is nothing to play on for.
+        let cube = CubeInfo::from(&no_gammons(0.95));
+        // Then
+        assert_eq!(cube.cube_action(), CubeAction::DoublePass);
+    }
+
+    #[test]
+    fn cube_action_no_double_and_double_take() {
+        assert_eq!(
+            CubeInfo::from(&no_gammons(0.55)).cube_action(),
+            CubeAction::NoDouble
+        );
+        assert_eq!(
+            CubeInfo::from(&no_gammons(0.70)).cube_action(),
+            CubeAction::DoubleTake
+        );
+        assert_eq!(
+            CubeInfo::new(&no_gammons(0.95), CubePosition::OpponentOwned).cube_action(),
+            CubeAction::NoDouble
+        );
+    }
+
+    #[test]
+    fn rollout_recommendation_double_take() {
+        // Given
+        let rollout = CubeRolloutResult {
+            equity_no_double: 0.4,
+            equity_double_take: 0.6,
+            equity_double_pass: 1.0,
+        };
+        // When
+        let decision = rollout.recommendation();
+        // Then
+        assert_eq!(decision.action, CubeAction::DoubleTake);
+        assert!((decision.no_double_error - 0.2).abs() < 0.0001);
+        assert_eq!(decision.double_error, 0.0);
+        assert!((decision.response_error - 0.4).abs() < 0.0001);
+    }
+
+    #[test]
+    fn rollout_recommendation_no_double() {
+        // Given
+        let rollout = CubeRolloutResult {
+            equity_no_double: 0.2,
+            equity_double_take: 0.1,
+            equity_double_pass: 1.0,
+        };
+        // When
+        let decision = rollout.recommendation();
+        // Then
+        assert_eq!(decision.action, CubeAction::NoDouble);
+        assert_eq!(decision.no_double_error, 0.0);
+        assert!((decision.double_error - 0.1).abs() < 0.0001);
+    }
+
     #[test]
     fn from_probabilities_defaults_to_centered() {
         // `From<&Probabilities>` should behave like an initial double decision.
@@ -544,7 +919,7 @@ mod tests {
         let probs = no_gammons(0.70);
         let cube = CubeState {
             position: CubePosition::Owned,
-            value: 4,
+            level: 2,
         };
         let via_state = CubeInfo::for_state(&probs, cube, MatchState::Money);
         let direct = CubeInfo::new(&probs, CubePosition::Owned);
@@ -609,7 +984,7 @@ mod tests {
         // `x` may not double when the opponent owns the cube, even in a match.
         let cube = CubeState {
             position: CubePosition::OpponentOwned,
-            value: 2,
+            level: 1,
         };
         let info = CubeInfo::for_match(&no_gammons(0.80), cube, 5, 5, false);
         assert!(!info.double());
@@ -622,7 +997,7 @@ mod tests {
         // centered cube, which is worth more than the opponent owning it.
         let probs = no_gammons(0.60);
         let no_double = |position| {
-            CubeInfo::for_match(&probs, CubeState { position, value: 1 }, 5, 5, false)
+            CubeInfo::for_match(&probs, CubeState { position, level: 0 }, 5, 5, false)
                 .equity_no_double()
         };
         assert!(no_double(CubePosition::Owned) > no_double(CubePosition::Centered));
@@ -651,24 +1026,73 @@ mod tests {
     }
 
     #[test]
-    fn match_cube_value_zero_does_not_crash() {
-        // A degenerate cube value must be clamped, not send the take-point
-        // recursion into an infinite loop.
+    fn match_cube_level_huge_does_not_crash() {
+        // A degenerate cube level must be clamped, not send the take-point
+        // recursion into an infinite loop or overflow.
         let cube = CubeState {
             position: CubePosition::Centered,
-            value: 0,
+            level: u8::MAX,
         };
         let info = CubeInfo::for_match(&no_gammons(0.6), cube, 5, 5, false);
         assert!(info.equity_no_double().is_finite());
     }
 
+    #[test]
+    fn cube_redoubled_to_128() {
+        // Given
+        let mut cube = Cube::default();
+        assert!(!cube.was_turned());
+        // When
+        for _ in 0..7 {
+            cube.double().unwrap();
+            cube = cube.sides_switched();
+        }
+        // Then
+        assert_eq!(cube.level(), 7);
+        assert_eq!(cube.value(), 128);
+        assert!(cube.was_turned());
+        assert_eq!(cube.history().len(), 7);
+        assert_eq!(cube.state().position, CubePosition::Owned);
+    }
+
+    #[test]
+    fn cube_can_only_be_doubled_by_owner() {
+        // Given
+        let mut cube = Cube::default();
+        cube.double().unwrap();
+        // When
+        let result = cube.double();
+        // Then
+        assert!(result.is_err());
+        assert_eq!(cube.level(), 1);
+    }
+
+    #[test]
+    fn cube_value_saturates() {
+        let cube = CubeState {
+            position: CubePosition::Centered,
+            level: 40,
+        };
+        assert_eq!(cube.value(), u32::MAX);
+    }
+
+    #[test]
+    fn cube_state_with_value() {
+        let cube = CubeState::with_value(CubePosition::Owned, 64).unwrap();
+        assert_eq!(cube.level, 6);
+        assert_eq!(cube.value(), 64);
+        assert!(CubeState::with_value(CubePosition::Owned, 0).is_err());
+        assert!(CubeState::with_value(CubePosition::Owned, 3).is_err());
+        assert!(CubeState::with_value(CubePosition::Owned, 1 << 31).is_err());
+    }
+
     #[test]
     fn post_crawford_leader_cube_cannot_be_taken() {
         // Trailer with an odd away would normally be taken, but if the opponent
         // owns the cube `x` cannot double, so `accept` must be false too.
         let cube = CubeState {
             position: CubePosition::OpponentOwned,
-            value: 1,
+            level: 0,
         };
         let info = CubeInfo::for_match(&no_gammons(0.5), cube, 3, 1, false);
         assert!(!info.double());
@@ -0,0 +1,221 @@
+use crate::cube::{CubeState, MatchState};
+use crate::match_equity::{match_equity_after_loss, match_equity_after_win, position_equity};
+use engine::dice::Dice;
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+
+/// Everything besides the board and the dice that influences a checker play decision:
+/// the doubling cube and the score.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameContext {
+    pub cube: CubeState,
+    pub match_state: MatchState,
+}
+
+impl Default for GameContext {
+    /// A money game with a centered cube.
+    fn default() -> Self {
+        Self {
+            cube: CubeState::default(),
+            match_state: MatchState::Money,
+        }
+    }
+}
+
+impl GameContext {
+    #[inline]
+    pub fn new(cube: CubeState, match_state: MatchState) -> Self {
+        Self { cube, match_state }
+    }
+
+    /// The same context seen from the opponent's perspective: the cube and the away scores are switched.
+    pub fn sides_switched(&self) -> Self {
+        let match_state = match self.match_state {
+            MatchState::Money => MatchState::Money,
+            MatchState::Match {
+                x_away,
+                o_away,
+                crawford,
+            } => MatchState::Match {
+                x_away: o_away,
+                o_away: x_away,
+                crawford,
+            },
+        };
+        Self::new(self.cube.sides_switched(), match_state)
+    }
+
+    /// Value function for ranking positions in this context.
+    ///
+    /// In a money game this is the cubeless equity. In match play it is the match-winning
+    /// probability at the current cube value, so gammons are weighted by what they are worth
+    /// at this score: at a gammon-go score a gammon counts as much as winning the match.
+    /// In the Crawford game the trailer loses the match with any loss, so gammons against the
+    /// trailer don't count and only the wins matter. The match equity table already assumes
+    /// that the cube is dead during the Crawford game.
+    #[inline]
+    pub fn value(&self) -> Box<dyn Fn(&Probabilities) -> f32> {
+        match self.match_state {
+            MatchState::Money => Box::new(|p: &Probabilities| p.equity()),
+            MatchState::Match { x_away, o_away, .. } => {
+                let cube_value = self.cube.value();
+                Box::new(move |p: &Probabilities| position_equity(p, x_away, o_away, cube_value))
+            }
+        }
+    }
+
+    /// Gammon values for player `x` and the opponent at the current score and cube value.
+    ///
+    /// A gammon value is how much a gammon adds on top of a single game, measured in units of the
+    /// difference between winning and losing a single game. In a money game both are `1`. In match
+    /// play they are derived from the match equity table: At double match point gammons don't count,
+    /// so both are `0`. [GameContext::value] already weighs gammons this way.
+    pub fn gammon_value(&self) -> (f32, f32) {
+        match self.match_state {
+            MatchState::Money => (1.0, 1.0),
+            MatchState::Match { x_away, o_away, .. } => {
+                let c = self.cube.value();
+                let win = match_equity_after_win(x_away, o_away, c);
+                let win_gammon = match_equity_after_win(x_away, o_away, 2 * c);
+                let lose = match_equity_after_loss(x_away, o_away, c);
+                let lose_gammon = match_equity_after_loss(x_away, o_away, 2 * c);
+                let single_game = win - lose;
+                (
+                    (win_gammon - win) / single_game,
+                    (lose - lose_gammon) / single_game,
+                )
+            }
+        }
+    }
+}
+
+/// Checker play that takes the cube and the score into account.
+pub trait MatchEvaluator {
+    /// Like [`Evaluator::best_position_by_equity`], but ranks the moves with
+    /// [`GameContext::value`] instead of money equity.
+    fn best_position_match(&self, pos: &Position, dice: &Dice, ctx: &GameContext) -> Position;
+}
+
+impl<T: Evaluator> MatchEvaluator for T {
+    #[inline]
+    fn best_position_match(&self, pos: &Position, dice: &Dice, ctx: &GameContext) -> Position {
+        self.best_position(pos, dice, ctx.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{CubePosition, CubeState, MatchState};
+    use crate::game_context::{GameContext, MatchEvaluator};
+    use engine::dice::Dice;
+    use engine::evaluator::{Evaluator, EvaluatorFake};
+    use engine::pos;
+    use engine::position::Position;
+
+    fn gammonish_position() -> Position {
+        pos!(x 5:1, 3:1; o 20:2).sides_switched()
+    }
+
+    /// Test double. All moves lead to a safe single game win, except for `gammonish_position`
+    /// which has a lower equity but much better gammon chances.
+    fn evaluator_fake() -> EvaluatorFake {
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut fake = EvaluatorFake::with_default([0.4, 0.0, 0.0, 0.6, 0.0, 0.0].into());
+        fake.insert(gammonish_position(), [0.1, 0.4, 0.0, 0.2, 0.3, 0.0].into());
+        fake
+    }
+
+    #[test]
+    fn sides_switched_swaps_away_scores_and_cube_owner() {
+        let cube = CubeState::with_value(CubePosition::Owned, 2).unwrap();
+        let ctx = GameContext::new(cube, MatchState::from_away(5, 3, false).unwrap());
+        let expected = GameContext::new(
+            CubeState::with_value(CubePosition::OpponentOwned, 2).unwrap(),
+            MatchState::from_away(3, 5, false).unwrap(),
+        );
+        assert_eq!(ctx.sides_switched(), expected);
+        assert_eq!(ctx.sides_switched().sides_switched(), ctx);
+    }
+
+    #[test]
+    fn gammon_value_money_game() {
+        assert_eq!(GameContext::default().gammon_value(), (1.0, 1.0));
+    }
+
+    #[test]
+    fn gammon_value_double_match_point() {
+        let ctx = GameContext::new(
+            CubeState::default(),
+            MatchState::from_away(1, 1, false).unwrap(),
+        );
+        assert_eq!(ctx.gammon_value(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn gammon_value_gammon_go() {
+        // 2-away 1-away post-Crawford: a single game leads to double match point, a gammon wins the match.
+        // The opponent wins the match with any win, so gammons don't matter for it.
+        let ctx = GameContext::new(
+            CubeState::default(),
+            MatchState::from_away(2, 1, false).unwrap(),
+        );
+        let (x_gammon_value, o_gammon_value) = ctx.gammon_value();
+        assert!(x_gammon_value >= 1.0);
+        assert_eq!(o_gammon_value, 0.0);
+    }
+
+    #[test]
+    fn money_game_maximizes_equity() {
+        // Given
+        let evaluator = evaluator_fake();
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        // When
+        let best = evaluator.best_position_match(&pos, &dice, &GameContext::default());
+        // Then
+        assert_ne!(best, gammonish_position());
+        assert_eq!(best, evaluator.best_position_by_equity(&pos, &dice));
+    }
+
+    #[test]
+    fn crawford_trailer_ignores_gammon_risk() {
+        // Given
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        let risky = pos!(x 5:1, 3:1; o 20:2).sides_switched();
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        // The safe moves lose fewer gammons, the risky move wins more games.
+        let mut evaluator = EvaluatorFake::with_default([0.55, 0.0, 0.0, 0.45, 0.0, 0.0].into());
+        evaluator.insert(risky, [0.2, 0.3, 0.0, 0.5, 0.0, 0.0].into());
+        // 3-away 1-away in the Crawford game: `x` loses the match with any loss.
+        let crawford = GameContext::new(
+            CubeState::default(),
+            MatchState::from_away(3, 1, true).unwrap(),
+        );
+        // When
+        let money_best = evaluator.best_position_match(&pos, &dice, &GameContext::default());
+        let crawford_best = evaluator.best_position_match(&pos, &dice, &crawford);
+        // Then
+        assert_ne!(money_best, risky);
+        assert_eq!(crawford_best, risky);
+    }
+
+    #[test]
+    fn gammon_go_score_maximizes_win_and_gammon() {
+        // Given
+        let evaluator = evaluator_fake();
+        let pos = pos!(x 7:2; o 20:2);
+        let dice = Dice::new(4, 2);
+        // 2-away 1-away post-Crawford: a gammon wins the match just like a double would.
+        let ctx = GameContext::new(
+            CubeState::default(),
+            MatchState::from_away(2, 1, false).unwrap(),
+        );
+        // When
+        let best = evaluator.best_position_match(&pos, &dice, &ctx);
+        // Then
+        assert_eq!(best, gammonish_position());
+        assert_ne!(best, evaluator.best_position_by_equity(&pos, &dice));
+    }
+}
@@ -1,4 +1,9 @@
 pub mod bg_move;
 pub mod cube;
+pub mod game_context;
 pub mod match_equity;
+pub mod roll_analysis;
+#[cfg(feature = "wasm")]
+pub mod wasm;
 pub mod wildbg_api;
+pub mod xgid;
@@ -0,0 +1,94 @@
+use crate::bg_move::BgMove;
+use engine::dice::{ALL_21, Dice};
+use engine::evaluator::Evaluator;
+use engine::position::Position;
+
+/// The best move for one of the 21 distinct rolls, see [RollAnalysisExt::roll_analysis].
+#[derive(Debug, PartialEq)]
+pub struct RollAnalysis {
+    pub dice: Dice,
+    /// Standard notation of the best move like `24/18 13/11`.
+    pub notation: String,
+    /// The position after the best move, still from the perspective of player `x` who has moved.
+    pub position: Position,
+    /// Cubeless equity after the best move, from the perspective of player `x`.
+    pub equity: f32,
+    /// How likely the roll is: `1/36` for doubles, `2/36` for mixed rolls.
+    pub probability: f32,
+}
+
+/// Best move and equity of each roll with an [Evaluator].
+pub trait RollAnalysisExt {
+    /// The best move by equity for each of the 21 distinct rolls in `pos`, in the order of [ALL_21].
+    ///
+    /// Everything needed for a table with the best play for every number.
+    fn roll_analysis(&self, pos: &Position) -> Vec<RollAnalysis>;
+}
+
+impl<T: Evaluator + ?Sized> RollAnalysisExt for T {
+    fn roll_analysis(&self, pos: &Position) -> Vec<RollAnalysis> {
+        ALL_21
+            .iter()
+            .map(|(dice, amount)| {
+                let (position, probabilities) = self
+                    .positions_and_probabilities_by_equity(pos, dice)
+                    .swap_remove(0);
+                RollAnalysis {
+                    dice: *dice,
+                    notation: BgMove::new(pos, &position, dice).to_string(),
+                    position,
+                    equity: probabilities.equity(),
+                    probability: *amount as f32 / 36.0,
+                }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::roll_analysis::RollAnalysisExt;
+    use engine::dice::Dice;
+    use engine::evaluator::EvaluatorFake;
+    use engine::pos;
+    use engine::position::STARTING;
+
+    #[test]
+    fn one_entry_for_each_roll_weighted_by_probability() {
+        // Given
+        let evaluator = EvaluatorFake::with_default([0.5, 0.0, 0.0, 0.5, 0.0, 0.0].into());
+        // When
+        let analysis = evaluator.roll_analysis(&STARTING);
+        // Then
+        assert_eq!(analysis.len(), 21);
+        for roll in &analysis {
+            if roll.dice.is_double() {
+                assert_eq!(roll.probability, 1.0 / 36.0);
+            } else {
+                assert_eq!(roll.probability, 2.0 / 36.0);
+            }
+        }
+        let total: f32 = analysis.iter().map(|roll| roll.probability).sum();
+        assert!((total - 1.0).abs() < 0.0001);
+    }
+
+    #[test]
+    fn best_move_with_notation_and_equity() {
+        // Given
+        let pos = pos!(x 7:2; o 20:2);
+        let best = pos!(x 5:1, 3:1; o 20:2);
+        // Probabilities are from the perspective of the opponent, who is on roll after our move.
+        let mut evaluator = EvaluatorFake::with_default([0.38, 0.2, 0.1, 0.12, 0.1, 0.1].into());
+        evaluator.insert(best.sides_switched(), [0.5, 0.1, 0.1, 0.1, 0.1, 0.1].into());
+        // When
+        let analysis = evaluator.roll_analysis(&pos);
+        // Then
+        let four_two = analysis
+            .iter()
+            .find(|roll| roll.dice == Dice::new(4, 2))
+            .unwrap();
+        assert_eq!(four_two.notation, "7/3 7/5");
+        assert_eq!(four_two.position, best);
+        assert!((four_two.equity + 0.4).abs() < 0.0001);
+    }
+}
@@ -0,0 +1,87 @@
+//! Bindings for running wildbg in the browser, enabled by the `wasm` feature.
+//!
+//! Build with `wasm-pack build crates/logic --features wasm`. Both neural nets are embedded
+//! into the binary, so no files need to be fetched at runtime.
+
+use crate::wildbg_api::{ScoreConfig, WildbgApi};
+use engine::composite::CompositeEvaluator;
+use engine::dice::Dice;
+use engine::position::Position;
+use engine::probabilities::Probabilities;
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    // Loading the nets is expensive, so it's only done once per thread (which is once in a browser).
+    static API: WildbgApi<CompositeEvaluator> =
+        WildbgApi::try_default().expect("Embedded neural nets should be loadable.");
+}
+
+/// Cubeless probabilities as plain JavaScript object. All numbers add up to 1.
+#[derive(Serialize)]
+struct ProbabilitiesJs {
+    win_normal: f32,
+    win_gammon: f32,
+    win_bg: f32,
+    lose_normal: f32,
+    lose_gammon: f32,
+    lose_bg: f32,
+}
+
+impl From<Probabilities> for ProbabilitiesJs {
+    fn from(value: Probabilities) -> Self {
+        Self {
+            win_normal: value.win_normal,
+            win_gammon: value.win_gammon,
+            win_bg: value.win_bg,
+            lose_normal: value.lose_normal,
+            lose_gammon: value.lose_gammon,
+            lose_bg: value.lose_bg,
+        }
+    }
+}
+
+/// Best move in a money game for the player on roll, for example `8/5 6/5`.
+///
+/// `position_id` is a GnuBG Position ID. Panics if the ID or the dice are invalid.
+#[wasm_bindgen]
+pub fn best_move_for(position_id: &str, die1: usize, die2: usize) -> String {
+    let position = Position::from_id(position_id);
+    let dice = Dice::try_new(die1, die2).expect("Dice must be between 1 and 6.");
+    API.with(|api| {
+        api.best_move(&position, &dice, &ScoreConfig::MoneyGame)
+            .to_string()
+    })
+}
+
+/// Cubeless probabilities for the player on roll in the position with the given GnuBG Position ID.
+#[wasm_bindgen]
+pub fn evaluate(position_id: &str) -> Result<JsValue, JsValue> {
+    let position = Position::from_id(position_id);
+    let probabilities = API.with(|api| api.probabilities(&position));
+    serde_wasm_bindgen::to_value(&ProbabilitiesJs::from(probabilities)).map_err(JsValue::from)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use crate::bg_move::BgMove;
+    use crate::wasm::best_move_for;
+    use engine::dice::Dice;
+    use engine::position::STARTING;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    #[wasm_bindgen_test]
+    fn starting_position_returns_legal_opening_move() {
+        // Given
+        let dice = Dice::new(3, 1);
+        let legal_moves: Vec<String> = STARTING
+            .all_positions_after_moving(&dice)
+            .iter()
+            .map(|new| BgMove::new(&STARTING, &new.sides_switched(), &dice).to_string())
+            .collect();
+        // When
+        let notation = best_move_for(&STARTING.position_id(), 3, 1);
+        // Then
+        assert!(legal_moves.contains(&notation));
+    }
+}
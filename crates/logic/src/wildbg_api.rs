@@ -94,6 +94,18 @@ impl<T: Evaluator> WildbgApi<T> {
     ) -> CubeInfo {
         CubeInfo::for_state(&self.evaluator.eval(position), cube, match_state)
     }
+
+    /// `true` if the opponent should beaver when player `x` doubles in `position` in a money game.
+    ///
+    /// The cube is centered before the double, see [CubeInfo::should_beaver].
+    pub fn should_beaver(&self, position: &Position) -> bool {
+        CubeInfo::for_state(
+            &self.evaluator.eval(position),
+            CubeState::default(),
+            MatchState::Money,
+        )
+        .should_beaver()
+    }
 }
 
 #[cfg(test)]
@@ -131,6 +143,7 @@ mod tests {
         // Then
         let expected_move = BgMove {
             details: vec![MoveDetail { from: 7, to: 5 }, MoveDetail { from: 5, to: 1 }],
+            hits: vec![],
         };
         assert_eq!(bg_move, expected_move);
     }
@@ -147,6 +160,7 @@ mod tests {
         // Then
         let expected_move = BgMove {
             details: vec![MoveDetail { from: 7, to: 3 }, MoveDetail { from: 7, to: 5 }],
+            hits: vec![],
         };
         assert_eq!(bg_move, expected_move);
     }
@@ -165,6 +179,7 @@ mod tests {
         // Then
         let expected_move = BgMove {
             details: vec![MoveDetail { from: 7, to: 3 }, MoveDetail { from: 7, to: 5 }],
+            hits: vec![],
         };
         assert_eq!(bg_move, expected_move);
     }
@@ -0,0 +1,189 @@
+//! eXtreme Gammon IDs like `XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10`.
+//!
+//! The fields are separated by `:`:
+//! board, cube exponent, cube owner, turn, dice, score of the bottom player, score of the top player,
+//! Crawford (or Jacoby in money games), match length and maximum cube exponent.
+//! The board is parsed by [Position::from_xgid], this module handles the other fields.
+
+use crate::cube::{CubePosition, CubeState, MatchState};
+use crate::game_context::GameContext;
+use engine::dice::Dice;
+use engine::position::Position;
+
+/// Maximum cube exponent written into an XGID, the default of eXtreme Gammon.
+const MAX_CUBE_EXPONENT: u8 = 10;
+
+/// Writing a [Position] as XGID.
+pub trait ToXgid {
+    /// XGID with player `x` as the bottom player on turn, who hasn't rolled yet.
+    ///
+    /// [GameContext] only knows the away scores. The match length in the XGID is the larger one,
+    /// so a 3-away 5-away match is written as 5 point match at 2:0.
+    fn to_xgid(&self, ctx: &GameContext) -> String;
+}
+
+impl ToXgid for Position {
+    fn to_xgid(&self, ctx: &GameContext) -> String {
+        let owner = match ctx.cube.position {
+            CubePosition::Centered => 0,
+            CubePosition::Owned => 1,
+            CubePosition::OpponentOwned => -1,
+        };
+        let (x_score, o_score, crawford, length) = match ctx.match_state {
+            MatchState::Money => (0, 0, false, 0),
+            MatchState::Match {
+                x_away,
+                o_away,
+                crawford,
+            } => {
+                let length = x_away.max(o_away);
+                (length - x_away, length - o_away, crawford, length)
+            }
+        };
+        format!(
+            "XGID={}:{}:{}:1:00:{}:{}:{}:{}:{}",
+            self.xgid_board(),
+            ctx.cube.level,
+            owner,
+            x_score,
+            o_score,
+            u8::from(crawford),
+            length,
+            MAX_CUBE_EXPONENT
+        )
+    }
+}
+
+impl GameContext {
+    /// Cube and score of an XGID, from the perspective of the player on turn like [Position::from_xgid].
+    pub fn from_xgid(xgid: &str) -> Result<Self, &'static str> {
+        let fields = fields(xgid)?;
+        let bottom_on_turn = bottom_on_turn(fields[3])?;
+        let level = fields[1]
+            .parse::<u8>()
+            .map_err(|_| "The cube exponent of an XGID must be a small number.")?;
+        let position = match (fields[2], bottom_on_turn) {
+            ("0", _) => CubePosition::Centered,
+            ("1", true) | ("-1", false) => CubePosition::Owned,
+            ("1", false) | ("-1", true) => CubePosition::OpponentOwned,
+            _ => return Err("The cube owner of an XGID must be 0, 1 or -1."),
+        };
+        let cube = CubeState::with_value(position, 1 << level.min(31))?;
+
+        let number = |field: &str| {
+            field
+                .parse::<u32>()
+                .map_err(|_| "Score and match length of an XGID must be numbers.")
+        };
+        let (bottom_score, top_score, length) =
+            (number(fields[5])?, number(fields[6])?, number(fields[8])?);
+        let match_state = if length == 0 {
+            MatchState::Money
+        } else {
+            if bottom_score >= length || top_score >= length {
+                return Err("The scores of an XGID must be lower than the match length.");
+            }
+            let (x_score, o_score) = if bottom_on_turn {
+                (bottom_score, top_score)
+            } else {
+                (top_score, bottom_score)
+            };
+            MatchState::from_away(length - x_score, length - o_score, fields[7] == "1")?
+        };
+        Ok(GameContext::new(cube, match_state))
+    }
+}
+
+/// The dice of an XGID. `None` if the player on turn hasn't rolled yet, for example `00` or a cube action.
+pub fn dice_from_xgid(xgid: &str) -> Result<Option<Dice>, &'static str> {
+    let dice = fields(xgid)?[4].as_bytes();
+    match dice {
+        [die1 @ b'1'..=b'6', die2 @ b'1'..=b'6'] => Ok(Some(Dice::new(
+            (die1 - b'0') as usize,
+            (die2 - b'0') as usize,
+        ))),
+        [b'0', b'0'] | [b'D'] | [b'B'] | [b'R'] => Ok(None),
+        _ => Err("The dice of an XGID must be two digits between 1 and 6, 00, D, B or R."),
+    }
+}
+
+fn fields(xgid: &str) -> Result<Vec<&str>, &'static str> {
+    let fields: Vec<&str> = xgid
+        .strip_prefix("XGID=")
+        .unwrap_or(xgid)
+        .split(':')
+        .collect();
+    if fields.len() < 9 {
+        return Err("An XGID needs at least 9 fields separated by ':'.");
+    }
+    Ok(fields)
+}
+
+fn bottom_on_turn(turn: &str) -> Result<bool, &'static str> {
+    match turn {
+        "1" => Ok(true),
+        "-1" => Ok(false),
+        _ => Err("The turn of an XGID must be 1 or -1."),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cube::{CubePosition, CubeState, MatchState};
+    use crate::game_context::GameContext;
+    use crate::xgid::{ToXgid, dice_from_xgid};
+    use engine::dice::Dice;
+    use engine::position::{Position, STARTING};
+
+    #[test]
+    fn starting_position_money_game() {
+        // Given
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:0:0:0:0:10";
+        // When
+        let position = Position::from_xgid(xgid).unwrap();
+        let ctx = GameContext::from_xgid(xgid).unwrap();
+        // Then
+        assert_eq!(position, STARTING);
+        assert_eq!(ctx, GameContext::default());
+        assert_eq!(dice_from_xgid(xgid), Ok(None));
+        assert_eq!(position.to_xgid(&ctx), xgid);
+    }
+
+    #[test]
+    fn match_with_top_player_on_turn() {
+        // Given
+        let xgid = "XGID=-BBBBBC------------bbbbbb-:1:1:-1:52:3:2:0:5:10";
+        // When
+        let position = Position::from_xgid(xgid).unwrap();
+        let ctx = GameContext::from_xgid(xgid).unwrap();
+        // Then
+        // The top player is on turn and needs 3 points, the bottom player owns the cube and needs 2.
+        let cube = CubeState::with_value(CubePosition::OpponentOwned, 2).unwrap();
+        let match_state = MatchState::from_away(3, 2, false).unwrap();
+        assert_eq!(ctx, GameContext::new(cube, match_state));
+        assert_eq!(dice_from_xgid(xgid), Ok(Some(Dice::new(5, 2))));
+        let written = position.to_xgid(&ctx);
+        assert_eq!(
+            written,
+            "XGID=-BBBBBB------------cbbbbb-:1:-1:1:00:0:1:0:3:10"
+        );
+        assert_eq!(Position::from_xgid(&written), Ok(position));
+        assert_eq!(GameContext::from_xgid(&written), Ok(ctx));
+    }
+
+    #[test]
+    fn crawford_game() {
+        let xgid = "XGID=-b----E-C---eE---c-e----B-:0:0:1:00:6:4:1:7:10";
+        let ctx = GameContext::from_xgid(xgid).unwrap();
+        assert_eq!(ctx.match_state, MatchState::from_away(1, 3, true).unwrap());
+    }
+
+    #[test]
+    fn invalid_fields() {
+        let start = "-b----E-C---eE---c-e----B-";
+        assert!(GameContext::from_xgid(&format!("XGID={start}:0:2:1:00:0:0:0:0:10")).is_err());
+        assert!(GameContext::from_xgid(&format!("XGID={start}:0:0:1:00:7:0:0:7:10")).is_err());
+        assert!(GameContext::from_xgid(&format!("XGID={start}:0:0")).is_err());
+        assert!(dice_from_xgid(&format!("XGID={start}:0:0:1:70:0:0:0:0:10")).is_err());
+    }
+}
@@ -4,7 +4,7 @@ use engine::evaluator::Evaluator;
 use engine::position::Position;
 use hyper::StatusCode;
 use logic::bg_move::{BgMove, MoveDetail};
-use logic::cube::{CubeInfo, CubePosition, CubeState, MAX_CUBE_VALUE, MatchState};
+use logic::cube::{CubeInfo, CubePosition, CubeState, MatchState};
 use logic::wildbg_api::{ScoreConfig, WildbgApi};
 use serde::{Deserialize, Serialize};
 use utoipa::{IntoParams, ToSchema};
@@ -301,11 +301,7 @@ impl TryFrom<&CubeParams> for CubeState {
             Some("opponent") => CubePosition::OpponentOwned,
             Some(_) => return Err("cube_position must be one of: centered, owned, opponent"),
         };
-        let value = params.cube_value.unwrap_or(1);
-        if value == 0 || !value.is_power_of_two() || value > MAX_CUBE_VALUE {
-            return Err("cube_value must be a positive power of two: 1, 2, 4, …");
-        }
-        Ok(CubeState { position, value })
+        CubeState::with_value(position, params.cube_value.unwrap_or(1))
     }
 }
 
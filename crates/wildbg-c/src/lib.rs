@@ -297,7 +297,8 @@ pub extern "C" fn probabilities(wildbg: &Wildbg, pips: &[c_int; 26]) -> CProbabi
 /// owns it. Any other value is treated as a centered cube.
 ///
 /// `cube_value` is the current value of the doubling cube (1, 2, 4, …). Values
-/// below 1 are treated as 1. It only affects match play.
+/// below 1 are treated as 1, other values are rounded down to a power of two.
+/// It only affects match play.
 ///
 /// `config` supplies the match score: `x_away`/`o_away` of `0` (both) means a
 /// money game, otherwise it is match play at that score, honouring `crawford`.
@@ -321,7 +322,7 @@ pub extern "C" fn cube_info(
     };
     let cube = CubeState {
         position,
-        value: cube_value.max(1) as u32,
+        level: (cube_value.max(1) as u32).ilog2() as u8,
     };
     let cube_result = || -> Result<CCubeInfo, Error> {
         let position = Position::try_from(pips)?;
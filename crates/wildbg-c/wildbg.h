@@ -128,7 +128,8 @@ struct CMove best_move(const struct Wildbg *wildbg,
  * owns it. Any other value is treated as a centered cube.
  *
  * `cube_value` is the current value of the doubling cube (1, 2, 4, …). Values
- * below 1 are treated as 1. It only affects match play.
+ * below 1 are treated as 1, other values are rounded down to a power of two.
+ * It only affects match play.
  *
  * `config` supplies the match score: `x_away`/`o_away` of `0` (both) means a
  * money game, otherwise it is match play at that score, honouring `crawford`.